@@ -0,0 +1,164 @@
+use api_bindium::ApiRequestError;
+use snafu::ResultExt as _;
+use snafu::Snafu;
+
+use crate::api::ListenBrainzAPIEnpoints;
+use crate::api::RequestBuildingError;
+use crate::api::delete_listen::DeleteListenBody;
+use crate::api::delete_listen::DeleteListenResponse;
+use crate::api::user::username::listens::UserListensListen;
+use crate::client::ListenBrainzClient;
+use crate::inner_macros::pg_counted;
+use crate::inner_macros::pg_inc;
+use crate::models::token::UserToken;
+use crate::parser::ResponseError;
+
+#[bon::bon]
+impl ListenBrainzAPIEnpoints {
+    /// Delete multiple listens of the user owning the token.
+    ///
+    /// A failing deletion doesn't stop the others. Instead, the failed listens are returned in the [`ListenDeletionReport`]
+    #[builder]
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(client, token, listens), fields(indicatif.pb_show = tracing::field::Empty)))]
+    pub async fn delete_listens(
+        client: &ListenBrainzClient,
+        token: UserToken,
+        listens: Vec<UserListensListen>,
+    ) -> ListenDeletionReport {
+        pg_counted!(listens.len(), "Deleting listens");
+
+        let mut report = ListenDeletionReport {
+            deleted_count: 0,
+            failed_deletions: Vec::new(),
+        };
+
+        for listen in listens {
+            match delete_listen(client, token.clone(), DeleteListenBody::from(&listen)).await {
+                Ok(_) => report.deleted_count += 1,
+                Err(error) => report
+                    .failed_deletions
+                    .push(FailedListenDeletion { listen, error }),
+            }
+
+            pg_inc!();
+        }
+
+        report
+    }
+}
+
+async fn delete_listen(
+    client: &ListenBrainzClient,
+    token: UserToken,
+    listen: DeleteListenBody,
+) -> Result<DeleteListenResponse, ListenDeletionError> {
+    let mut request = client
+        .endpoints()
+        .post_delete_listen(listen, token)
+        .context(RequestBuildingSnafu)?;
+
+    client
+        .send_authenticated_async(&mut request)
+        .await
+        .context(ApiRequestSnafu)?
+        .parse()
+        .context(ResponseSnafu)
+}
+
+/// The result of [`ListenBrainzAPIEnpoints::delete_listens`]
+#[derive(Debug)]
+pub struct ListenDeletionReport {
+    /// The number of listens successfully deleted
+    pub deleted_count: usize,
+
+    /// The listens that couldn't be deleted
+    pub failed_deletions: Vec<FailedListenDeletion>,
+}
+
+impl ListenDeletionReport {
+    /// Return true if all the listens were deleted
+    pub fn is_success(&self) -> bool {
+        self.failed_deletions.is_empty()
+    }
+}
+
+/// A listen that couldn't be deleted
+#[derive(Debug)]
+pub struct FailedListenDeletion {
+    /// The listen that couldn't be deleted
+    pub listen: UserListensListen,
+
+    /// Why the deletion failed
+    pub error: ListenDeletionError,
+}
+
+#[derive(Debug, Snafu)]
+pub enum ListenDeletionError {
+    RequestBuildingError {
+        source: RequestBuildingError,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+
+    ApiRequestError {
+        source: ApiRequestError,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+
+    ResponseError {
+        source: ResponseError,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+}
+
+#[cfg(test)]
+mod test {
+    use macro_rules_attribute::apply;
+
+    use crate::api::ListenBrainzAPIEnpoints;
+    use crate::mock_server::MockListenBrainzServer;
+    use crate::mock_server::data::FIXTURE_TOKEN;
+    use crate::mock_server::data::FIXTURE_USER;
+    use crate::models::token::UserToken;
+
+    #[apply(smol_macros::test!)]
+    async fn delete_listens_test() {
+        let server = MockListenBrainzServer::start_with_fixtures().unwrap();
+        let client = server.client();
+        let listens = server.data().users[FIXTURE_USER].listens[..3].to_vec();
+
+        // The first deletion is refused
+        server.data().queued_errors.push_back(400);
+        let report = ListenBrainzAPIEnpoints::delete_listens()
+            .client(&client)
+            .token(UserToken::from(FIXTURE_TOKEN.to_string()))
+            .listens(listens.clone())
+            .call()
+            .await;
+
+        assert!(!report.is_success());
+        assert_eq!(report.deleted_count, 2);
+        assert_eq!(report.failed_deletions.len(), 1);
+        assert_eq!(report.failed_deletions[0].listen, listens[0]);
+
+        let data = server.data();
+        let remaining = &data.users[FIXTURE_USER].listens;
+        assert!(remaining.contains(&listens[0]));
+        assert!(!remaining.contains(&listens[1]));
+        assert!(!remaining.contains(&listens[2]));
+    }
+}
@@ -0,0 +1,100 @@
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::api::ListenBrainzAPIEnpoints;
+use crate::api::RequestBuildingError;
+use crate::api::user::username::listens::UserListensListen;
+use crate::models::mbid::RecordingMsid;
+use crate::models::token::AuthenticatedRequest;
+use crate::models::token::UserToken;
+use crate::parser::ListenBrainzParser;
+
+#[cfg(feature = "async")]
+pub mod bulk;
+
+impl ListenBrainzAPIEnpoints {
+    /// Delete a listen of the user owning the token.
+    ///
+    /// The listen is identified by its `listened_at` timestamp and `recording_msid`
+    pub fn post_delete_listen(
+        &self,
+        listen: DeleteListenBody,
+        token: UserToken,
+    ) -> Result<AuthenticatedRequest<ListenBrainzParser<DeleteListenResponse>>, RequestBuildingError>
+    {
+        let request = self.json_post_request("/1/delete-listen", &listen)?;
+
+        Ok(AuthenticatedRequest::new(request, token))
+    }
+}
+
+// === Argument types ===
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct DeleteListenBody {
+    /// The timestamp of the listen to delete
+    pub listened_at: i64,
+
+    /// The MSID of the listen to delete
+    pub recording_msid: RecordingMsid,
+}
+
+impl From<&UserListensListen> for DeleteListenBody {
+    fn from(value: &UserListensListen) -> Self {
+        Self {
+            listened_at: value.listened_at,
+            recording_msid: value.recording_msid.clone(),
+        }
+    }
+}
+
+impl From<UserListensListen> for DeleteListenBody {
+    fn from(value: UserListensListen) -> Self {
+        Self {
+            listened_at: value.listened_at,
+            recording_msid: value.recording_msid,
+        }
+    }
+}
+
+// === Response types ===
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct DeleteListenResponse {
+    pub status: String,
+}
+
+#[cfg(test)]
+mod test {
+    #[cfg(feature = "async")]
+    use macro_rules_attribute::apply;
+
+    #[cfg(feature = "async")]
+    #[apply(smol_macros::test!)]
+    async fn delete_listen_test() {
+        use crate::api::delete_listen::DeleteListenBody;
+        use crate::mock_server::MockListenBrainzServer;
+        use crate::mock_server::data::FIXTURE_TOKEN;
+        use crate::mock_server::data::FIXTURE_USER;
+        use crate::models::token::UserToken;
+
+        let server = MockListenBrainzServer::start_with_fixtures().unwrap();
+        let client = server.client();
+        let listen = server.data().users[FIXTURE_USER].listens[0].clone();
+        let listen_count = server.data().users[FIXTURE_USER].listens.len();
+
+        let res = client
+            .post_delete_listen_async(
+                DeleteListenBody::from(&listen),
+                UserToken::from(FIXTURE_TOKEN.to_string()),
+            )
+            .await
+            .unwrap();
+        assert_eq!(res.status, "ok");
+
+        let data = server.data();
+        let listens = &data.users[FIXTURE_USER].listens;
+        assert_eq!(listens.len(), listen_count - 1);
+        assert!(!listens.contains(&listen));
+    }
+}
@@ -1,37 +1,31 @@
-use api_bindium::ApiRequest;
-use api_bindium::HTTPVerb;
-use api_bindium::JsonParser;
-use api_bindium::endpoints::UriBuilderError;
-
 use crate::api::ListenBrainzAPIEnpoints;
+use crate::api::RequestBuildingError;
+use crate::models::mbid::RecordingMbid;
+use crate::models::mbid::RecordingMsid;
+use crate::models::token::AuthenticatedRequest;
 use crate::models::token::UserToken;
+use crate::parser::ListenBrainzParser;
 
 impl ListenBrainzAPIEnpoints {
     pub fn post_submit_manual_mapping(
         &self,
         mapping: SubmitManualMappingBody,
         token: UserToken,
-    ) -> Result<ApiRequest<JsonParser<Vec<SubmitManualMappingBody>>>, UriBuilderError> {
-        let mut request = self
-            .endpoint_builder()
-            .set_path("/1/metadata/submit_manual_mapping/")
-            .into_api_request_with_body(
-                HTTPVerb::Post,
-                serde_json::to_value(mapping).unwrap(),
-                JsonParser::default(),
-            )?;
-
-        token.add_authorization(&mut request);
+    ) -> Result<
+        AuthenticatedRequest<ListenBrainzParser<Vec<SubmitManualMappingBody>>>,
+        RequestBuildingError,
+    > {
+        let request = self.json_post_request("/1/metadata/submit_manual_mapping/", &mapping)?;
 
-        Ok(request)
+        Ok(AuthenticatedRequest::new(request, token))
     }
 }
 
 #[derive(serde::Serialize, serde::Deserialize)]
 pub struct SubmitManualMappingBody {
     /// The MSID to map
-    recording_msid: String,
+    pub recording_msid: RecordingMsid,
 
     /// The MBID to map
-    recording_mbid: String,
+    pub recording_mbid: RecordingMbid,
 }
@@ -1,31 +1,104 @@
+use api_bindium::ApiRequest;
+use api_bindium::HTTPVerb;
 use api_bindium::endpoints::EndpointUriBuilder;
+use api_bindium::endpoints::UriBuilderError;
 use api_bindium::endpoints::path::EndpointUriBuilderPath;
+use api_bindium::ureq::http::Uri;
+use api_bindium::ureq::http::uri::InvalidUri;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use snafu::OptionExt as _;
+use snafu::ResultExt as _;
+use snafu::Snafu;
 
+use crate::parser::ListenBrainzParser;
+
+pub mod delete_listen;
 pub mod metadata;
 pub mod popularity;
+pub mod submit_listens;
 pub mod user;
 pub mod validate_token;
 
 #[derive(Debug, bon::Builder, Clone)]
 pub struct ListenBrainzAPIEnpoints {
-    /// The domain of the listenbrainz server.
+    /// The domain of the listenbrainz server, with its port if it isn't the default one.
     ///
-    /// Please note that all the api endpoints must be accessed by HTTPS.
+    /// Please note that the official server must be accessed by HTTPS.
     #[builder(default = "api.listenbrainz.org".to_string())]
     lb_domain: String,
+
+    /// The scheme used to reach the server. Default to `https`.
+    ///
+    /// Plain `http` is only meant for local servers, like a development instance or the ones of [`crate::mock_server`]
+    #[builder(default = "https".to_string())]
+    scheme: String,
+
+    /// The path the api is mounted under, if it isn't served at the root of the domain. Example: `/listenbrainz/api`
+    #[builder(default)]
+    path_prefix: String,
 }
 
 impl ListenBrainzAPIEnpoints {
+    /// Create the endpoints of the server at `base_url`.
+    ///
+    /// The url can contain a port and a path prefix. Example: `http://localhost:8100` or `https://example.com/listenbrainz/api`
+    pub fn from_base_url(base_url: &str) -> Result<Self, BaseUrlError> {
+        let uri = base_url
+            .parse::<Uri>()
+            .context(InvalidBaseUrlSnafu { base_url })?;
+
+        let scheme = uri.scheme_str().context(MissingSchemeSnafu { base_url })?;
+        let authority = uri
+            .authority()
+            .context(MissingAuthoritySnafu { base_url })?;
+
+        Ok(Self::builder()
+            .scheme(scheme.to_string())
+            .lb_domain(authority.to_string())
+            .path_prefix(uri.path().trim_end_matches('/').to_string())
+            .build())
+    }
+
     /// The api root
     pub fn api_root(&self) -> String {
-        format!("https://{}", self.lb_domain)
+        format!(
+            "{}://{}{}",
+            self.scheme,
+            self.lb_domain,
+            self.path_prefix.trim_end_matches('/')
+        )
     }
 
-    /// Return the root Uri for the
+    /// Return the root Uri for the endpoints. The paths added to it are appended to the path prefix of the server
     pub fn endpoint_builder(&self) -> EndpointUriBuilder<EndpointUriBuilderPath> {
-        EndpointUriBuilder::new()
-            .https()
-            .set_authority(&self.lb_domain)
+        let builder = EndpointUriBuilder::new()
+            .set_scheme(&self.scheme)
+            .set_authority(&self.lb_domain);
+
+        if self.path_prefix.is_empty() {
+            return builder;
+        }
+
+        builder.add_path_fragment(self.path_prefix.trim_end_matches('/'))
+    }
+
+    /// Create a POST request to the endpoint at `path`, with `body` serialized as its JSON body
+    pub fn json_post_request<B, T>(
+        &self,
+        path: &str,
+        body: &B,
+    ) -> Result<ApiRequest<ListenBrainzParser<T>>, RequestBuildingError>
+    where
+        B: Serialize + ?Sized,
+        T: DeserializeOwned,
+    {
+        let body = serde_json::to_value(body).context(BodySerializationSnafu)?;
+
+        self.endpoint_builder()
+            .set_path(path)
+            .into_api_request_with_body(HTTPVerb::Post, body, ListenBrainzParser::default())
+            .context(UriBuilderSnafu)
     }
 }
 
@@ -34,3 +107,104 @@ impl Default for ListenBrainzAPIEnpoints {
         Self::builder().build()
     }
 }
+
+#[derive(Debug, Snafu)]
+pub enum BaseUrlError {
+    #[snafu(display("`{base_url}` isn't a valid url"))]
+    InvalidBaseUrlError {
+        source: InvalidUri,
+        base_url: String,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+
+    #[snafu(display("The url `{base_url}` is missing its scheme (`http://` or `https://`)"))]
+    MissingSchemeError {
+        base_url: String,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+
+    #[snafu(display("The url `{base_url}` is missing its domain"))]
+    MissingAuthorityError {
+        base_url: String,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+}
+
+#[derive(Debug, Snafu)]
+pub enum RequestBuildingError {
+    UriBuilderError {
+        source: UriBuilderError,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+
+    #[snafu(display("The body of the request couldn't be serialized"))]
+    BodySerializationError {
+        source: serde_json::Error,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+}
+
+#[cfg(test)]
+mod test {
+    use crate::api::ListenBrainzAPIEnpoints;
+
+    #[test]
+    fn base_url_test() {
+        let endpoints =
+            ListenBrainzAPIEnpoints::from_base_url("http://localhost:8100/listenbrainz/api/")
+                .unwrap();
+
+        assert_eq!(
+            endpoints.api_root(),
+            "http://localhost:8100/listenbrainz/api"
+        );
+        assert_eq!(
+            endpoints
+                .get_user_username_listen_count("RustyNova")
+                .unwrap()
+                .uri()
+                .to_string(),
+            "http://localhost:8100/listenbrainz/api/1/user/RustyNova/listen-count"
+        );
+
+        let endpoints = ListenBrainzAPIEnpoints::default();
+        assert_eq!(endpoints.api_root(), "https://api.listenbrainz.org");
+        assert_eq!(
+            endpoints
+                .post_validate_token("abc".to_string().into())
+                .unwrap()
+                .request()
+                .uri()
+                .to_string(),
+            "https://api.listenbrainz.org/1/validate-token"
+        );
+
+        assert!(ListenBrainzAPIEnpoints::from_base_url("localhost:8100").is_err());
+        assert!(ListenBrainzAPIEnpoints::from_base_url("http://").is_err());
+    }
+}
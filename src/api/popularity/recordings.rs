@@ -1,33 +1,33 @@
 use api_bindium::ApiRequest;
-use api_bindium::HTTPVerb;
-use api_bindium::JsonParser;
-use api_bindium::endpoints::UriBuilderError;
 
 use crate::api::ListenBrainzAPIEnpoints;
+use crate::api::RequestBuildingError;
+use crate::models::mbid::RecordingMbid;
+use crate::parser::ListenBrainzParser;
 
 impl ListenBrainzAPIEnpoints {
     pub fn post_popularity_recording(
         &self,
-        recording_mbids: Vec<String>,
-    ) -> Result<ApiRequest<JsonParser<Vec<PopularityRecordingResponse>>>, UriBuilderError> {
-        self.endpoint_builder()
-            .set_path("/1/popularity/recording")
-            .into_api_request_with_body(
-                HTTPVerb::Post,
-                serde_json::to_value(PopularityRecordingQuery { recording_mbids }).unwrap(),
-                JsonParser::default(),
-            )
+        recording_mbids: Vec<RecordingMbid>,
+    ) -> Result<
+        ApiRequest<ListenBrainzParser<Vec<PopularityRecordingResponse>>>,
+        RequestBuildingError,
+    > {
+        self.json_post_request(
+            "/1/popularity/recording",
+            &PopularityRecordingQuery { recording_mbids },
+        )
     }
 }
 
 #[derive(serde::Serialize)]
 struct PopularityRecordingQuery {
-    recording_mbids: Vec<String>,
+    recording_mbids: Vec<RecordingMbid>,
 }
 
 #[derive(Debug, serde::Deserialize, serde::Serialize, Clone, PartialEq, Eq)]
 pub struct PopularityRecordingResponse {
-    pub recording_mbid: String,
+    pub recording_mbid: RecordingMbid,
     pub total_listen_count: Option<u64>,
     pub total_user_count: Option<u64>,
 }
@@ -38,28 +38,46 @@ mod test {
 
     use macro_rules_attribute::apply;
 
-    use crate::client::ListenBrainzClient;
+    use crate::client::cassette::fixture_cassette_client;
+    use crate::mock_server::MockListenBrainzServer;
+    use crate::models::mbid::RecordingMbid;
 
     #[apply(smol_macros::test!)]
 
     async fn post_popularity_recording_test() {
-        let client = ListenBrainzClient::default();
+        let server = MockListenBrainzServer::start_with_fixtures().unwrap();
+        let client = server.client();
 
-        let mut req = client
-            .endpoints()
-            .post_popularity_recording(vec!["61c54b0e-3a82-49af-9cc7-73ff34365697".to_string()])
+        let mut res = client
+            .post_popularity_recording_async(vec![
+                RecordingMbid::try_from("61c54b0e-3a82-49af-9cc7-73ff34365697").unwrap(),
+            ])
+            .await
             .unwrap();
-        let mut res = req
-            .send_async(client.api_client())
+
+        let res = res.pop().unwrap();
+        assert_eq!(
+            res.recording_mbid.as_str(),
+            "61c54b0e-3a82-49af-9cc7-73ff34365697"
+        );
+        assert!(res.total_listen_count.is_some());
+    }
+
+    #[apply(smol_macros::test!)]
+    async fn post_popularity_recording_cassette_test() {
+        let client = fixture_cassette_client("popularity");
+
+        let mut res = client
+            .post_popularity_recording_async(vec![
+                RecordingMbid::try_from("61c54b0e-3a82-49af-9cc7-73ff34365697").unwrap(),
+            ])
             .await
-            .unwrap()
-            .parse()
             .unwrap();
 
         let res = res.pop().unwrap();
         assert_eq!(
-            res.recording_mbid,
-            "61c54b0e-3a82-49af-9cc7-73ff34365697".to_string()
+            res.recording_mbid.as_str(),
+            "61c54b0e-3a82-49af-9cc7-73ff34365697"
         );
         assert!(res.total_listen_count.is_some());
     }
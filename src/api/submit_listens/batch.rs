@@ -0,0 +1,95 @@
+use snafu::ResultExt as _;
+
+use crate::api::BodySerializationSnafu;
+use crate::api::RequestBuildingError;
+use crate::api::submit_listens::SubmittedListen;
+use crate::api::submit_listens::validation::MAX_LISTEN_PAYLOAD_SIZE;
+use crate::api::submit_listens::validation::MAX_LISTENS_PER_REQUEST;
+
+/// The size reserved for the json around the listens (`listen_type`, `payload` key, brackets...)
+const PAYLOAD_OVERHEAD: usize = 64;
+
+/// Split listens into batches that respect the listen count and size limits of an `import` request.
+///
+/// Fail if a listen can't be serialized to compute its size
+pub fn batch_listens<I>(listens: I) -> Result<Vec<Vec<SubmittedListen>>, RequestBuildingError>
+where
+    I: IntoIterator<Item = SubmittedListen>,
+{
+    let mut batches = Vec::new();
+    let mut current = Vec::new();
+    let mut current_size = PAYLOAD_OVERHEAD;
+
+    for listen in listens {
+        // +1 for the comma between listens
+        let size = serde_json::to_vec(&listen)
+            .context(BodySerializationSnafu)?
+            .len()
+            + 1;
+
+        if !current.is_empty()
+            && (current.len() >= MAX_LISTENS_PER_REQUEST
+                || current_size + size > MAX_LISTEN_PAYLOAD_SIZE)
+        {
+            batches.push(core::mem::take(&mut current));
+            current_size = PAYLOAD_OVERHEAD;
+        }
+
+        current_size += size;
+        current.push(listen);
+    }
+
+    if !current.is_empty() {
+        batches.push(current);
+    }
+
+    Ok(batches)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::api::submit_listens::SubmitListensPayload;
+    use crate::api::submit_listens::SubmittedListen;
+    use crate::api::submit_listens::SubmittedTrackMetadata;
+    use crate::api::submit_listens::batch::batch_listens;
+    use crate::api::submit_listens::validation::MAX_LISTEN_PAYLOAD_SIZE;
+    use crate::api::submit_listens::validation::MAX_LISTENS_PER_REQUEST;
+
+    fn listen(track_name: String) -> SubmittedListen {
+        SubmittedListen::builder()
+            .listened_at(1_705_000_000)
+            .track_metadata(
+                SubmittedTrackMetadata::builder()
+                    .artist_name("Kikuo")
+                    .track_name(track_name)
+                    .build(),
+            )
+            .build()
+    }
+
+    #[test]
+    fn batch_listens_count_test() {
+        let listens = (0..2500).map(|i| listen(i.to_string()));
+        let batches = batch_listens(listens).unwrap();
+
+        assert_eq!(batches.len(), 3);
+        assert_eq!(batches[0].len(), MAX_LISTENS_PER_REQUEST);
+        assert_eq!(batches[2].len(), 500);
+    }
+
+    #[test]
+    fn batch_listens_size_test() {
+        // Big listens, so that a full batch of listens overflows the payload size
+        let listens = (0..1500).map(|i| listen(format!("{i}{}", "a".repeat(10_200))));
+        let batches = batch_listens(listens).unwrap();
+
+        for batch in &batches {
+            let size = serde_json::to_vec(&SubmitListensPayload::Import(batch.clone()))
+                .unwrap()
+                .len();
+            assert!(size <= MAX_LISTEN_PAYLOAD_SIZE);
+            assert!(batch.len() < MAX_LISTENS_PER_REQUEST);
+        }
+        assert_eq!(batches.iter().map(Vec::len).sum::<usize>(), 1500);
+    }
+}
@@ -0,0 +1,264 @@
+use api_bindium::ApiRequestError;
+use snafu::ResultExt as _;
+use snafu::Snafu;
+
+use crate::api::ListenBrainzAPIEnpoints;
+use crate::api::RequestBuildingError;
+use crate::api::submit_listens::SubmitListensError;
+use crate::api::submit_listens::SubmitListensPayload;
+use crate::api::submit_listens::SubmitListensResponse;
+use crate::api::submit_listens::SubmittedListen;
+use crate::api::submit_listens::batch::batch_listens;
+use crate::api::submit_listens::validation::InvalidListenError;
+use crate::client::ListenBrainzClient;
+use crate::inner_macros::pg_counted;
+use crate::inner_macros::pg_inc;
+use crate::models::token::UserToken;
+use crate::parser::ResponseError;
+
+#[bon::bon]
+impl ListenBrainzAPIEnpoints {
+    /// Import a large amount of listens, automatically splitting them into batches that respect the limits of the server.
+    ///
+    /// Each listen is validated first. The invalid listens are skipped, so they don't make the whole batch fail.
+    ///
+    /// A failing batch doesn't stop the import. Instead, the invalid listens and the failed batches are returned in the [`ListenImportReport`].
+    ///
+    /// Fail before sending anything if the listens can't be serialized
+    #[builder]
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(client, token, listens), fields(indicatif.pb_show = tracing::field::Empty)))]
+    pub async fn import_listens<I>(
+        client: &ListenBrainzClient,
+        token: UserToken,
+        listens: I,
+        /// How many batches can be sent at the same time. Default to 1 (sequential)
+        concurrency: Option<usize>,
+    ) -> Result<ListenImportReport, RequestBuildingError>
+    where
+        I: IntoIterator<Item = SubmittedListen>,
+    {
+        let mut invalid_listens = Vec::new();
+        let valid_listens = listens
+            .into_iter()
+            .enumerate()
+            .filter_map(|(index, listen)| match listen.validate(false) {
+                Ok(()) => Some(listen),
+                Err(error) => {
+                    invalid_listens.push(InvalidImportedListen {
+                        index,
+                        listen,
+                        error,
+                    });
+                    None
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let batches = batch_listens(valid_listens)?;
+        let concurrency = concurrency.unwrap_or(1).max(1);
+
+        pg_counted!(batches.len(), "Importing listens");
+
+        let mut report = ListenImportReport {
+            batch_count: batches.len(),
+            invalid_listens,
+            failed_batches: Vec::new(),
+        };
+
+        let mut batches = batches.into_iter().enumerate().peekable();
+        while batches.peek().is_some() {
+            let tasks = batches
+                .by_ref()
+                .take(concurrency)
+                .map(|(index, listens)| {
+                    let client = client.clone();
+                    let token = token.clone();
+
+                    async move {
+                        let res = submit_batch(&client, token, listens.clone()).await;
+                        (index, listens, res)
+                    }
+                })
+                .collect::<Vec<_>>();
+
+            let results = client.run_concurrently(tasks).await;
+
+            for (index, listens, res) in results {
+                if let Err(error) = res {
+                    report.failed_batches.push(FailedListenBatch {
+                        index,
+                        listens,
+                        error,
+                    });
+                }
+
+                pg_inc!();
+            }
+        }
+
+        Ok(report)
+    }
+}
+
+async fn submit_batch(
+    client: &ListenBrainzClient,
+    token: UserToken,
+    listens: Vec<SubmittedListen>,
+) -> Result<SubmitListensResponse, ListenBatchError> {
+    let mut request = client
+        .endpoints()
+        .post_submit_listens(SubmitListensPayload::Import(listens), token)
+        .context(SubmitListensSnafu)?;
+
+    client
+        .send_authenticated_async(&mut request)
+        .await
+        .context(ApiRequestSnafu)?
+        .parse()
+        .context(ResponseSnafu)
+}
+
+/// The result of [`ListenBrainzAPIEnpoints::import_listens`]
+#[derive(Debug)]
+pub struct ListenImportReport {
+    /// The total number of batches sent
+    pub batch_count: usize,
+
+    /// The listens that were skipped because they are invalid
+    pub invalid_listens: Vec<InvalidImportedListen>,
+
+    /// The batches that couldn't be imported
+    pub failed_batches: Vec<FailedListenBatch>,
+}
+
+impl ListenImportReport {
+    /// Return true if all the listens were imported
+    pub fn is_success(&self) -> bool {
+        self.invalid_listens.is_empty() && self.failed_batches.is_empty()
+    }
+}
+
+/// A listen that was skipped during an import
+#[derive(Debug)]
+pub struct InvalidImportedListen {
+    /// The index of the listen in the imported listens
+    pub index: usize,
+
+    /// The invalid listen
+    pub listen: SubmittedListen,
+
+    /// Why the listen is invalid
+    pub error: InvalidListenError,
+}
+
+/// A batch of listens that couldn't be imported
+#[derive(Debug)]
+pub struct FailedListenBatch {
+    /// The index of the batch in the import
+    pub index: usize,
+
+    /// The listens of the batch
+    pub listens: Vec<SubmittedListen>,
+
+    /// Why the batch failed
+    pub error: ListenBatchError,
+}
+
+#[derive(Debug, Snafu)]
+pub enum ListenBatchError {
+    SubmitListensError {
+        source: SubmitListensError,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+
+    ApiRequestError {
+        source: ApiRequestError,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+
+    ResponseError {
+        source: ResponseError,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+}
+
+#[cfg(test)]
+mod test {
+    use macro_rules_attribute::apply;
+
+    use crate::api::ListenBrainzAPIEnpoints;
+    use crate::api::submit_listens::SubmittedListen;
+    use crate::api::submit_listens::SubmittedTrackMetadata;
+    use crate::mock_server::MockListenBrainzServer;
+    use crate::mock_server::data::FIXTURE_TOKEN;
+    use crate::mock_server::data::FIXTURE_USER;
+    use crate::models::token::UserToken;
+
+    fn listen(listened_at: i64, track_name: &str) -> SubmittedListen {
+        SubmittedListen::builder()
+            .listened_at(listened_at)
+            .track_metadata(
+                SubmittedTrackMetadata::builder()
+                    .artist_name("Kikuo")
+                    .track_name(track_name)
+                    .build(),
+            )
+            .build()
+    }
+
+    #[apply(smol_macros::test!)]
+    async fn import_listens_test() {
+        let server = MockListenBrainzServer::start_with_fixtures().unwrap();
+        let client = server.client();
+        let listen_count = server.data().users[FIXTURE_USER].listens.len();
+
+        let mut listens = (0..2500)
+            .map(|i| listen(1_800_000_000 + i, "Aishite"))
+            .collect::<Vec<_>>();
+        listens.insert(10, listen(1_800_000_000, " "));
+        listens.insert(2000, listen(1_000, "Too old"));
+
+        let report = ListenBrainzAPIEnpoints::import_listens()
+            .client(&client)
+            .token(UserToken::from(FIXTURE_TOKEN.to_string()))
+            .listens(listens)
+            .concurrency(2)
+            .call()
+            .await
+            .unwrap();
+
+        // The invalid listens are skipped, and the others are imported
+        assert_eq!(report.batch_count, 3);
+        assert!(report.failed_batches.is_empty());
+        assert_eq!(
+            report
+                .invalid_listens
+                .iter()
+                .map(|invalid| invalid.index)
+                .collect::<Vec<_>>(),
+            [10, 2000]
+        );
+        assert!(!report.is_success());
+
+        assert_eq!(server.request_count(), 3);
+        assert_eq!(
+            server.data().users[FIXTURE_USER].listens.len(),
+            listen_count + 2500
+        );
+    }
+}
@@ -0,0 +1,168 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+use serde::Serialize;
+use serde::ser::SerializeStruct as _;
+use snafu::ResultExt as _;
+use snafu::Snafu;
+
+use crate::api::ListenBrainzAPIEnpoints;
+use crate::api::RequestBuildingError;
+use crate::api::submit_listens::validation::ListenValidationError;
+use crate::models::token::AuthenticatedRequest;
+use crate::models::token::UserToken;
+use crate::parser::ListenBrainzParser;
+
+pub mod batch;
+#[cfg(feature = "async")]
+pub mod import;
+pub mod validation;
+
+impl ListenBrainzAPIEnpoints {
+    /// Submit listens to the server, or update the "playing now" status of the user.
+    ///
+    /// The payload is validated against the documented limits of the server before creating the request,
+    /// so an invalid batch is refused before hitting the network.
+    pub fn post_submit_listens(
+        &self,
+        payload: SubmitListensPayload,
+        token: UserToken,
+    ) -> Result<AuthenticatedRequest<ListenBrainzParser<SubmitListensResponse>>, SubmitListensError>
+    {
+        payload.validate().context(ValidationSnafu)?;
+
+        let request = self
+            .json_post_request("/1/submit-listens", &payload)
+            .context(RequestBuildingSnafu)?;
+
+        Ok(AuthenticatedRequest::new(request, token))
+    }
+
+    /// Set the track the user owning the token is currently listening to.
+    ///
+    /// This is a shortcut for [`Self::post_submit_listens`] with a [`SubmitListensPayload::PlayingNow`] payload
+    pub fn post_submit_playing_now(
+        &self,
+        track_metadata: SubmittedTrackMetadata,
+        token: UserToken,
+    ) -> Result<AuthenticatedRequest<ListenBrainzParser<SubmitListensResponse>>, SubmitListensError>
+    {
+        self.post_submit_listens(
+            SubmitListensPayload::PlayingNow(
+                SubmittedListen::builder()
+                    .track_metadata(track_metadata)
+                    .build(),
+            ),
+            token,
+        )
+    }
+}
+
+// === Argument types ===
+
+/// The body of a `submit-listens` request.
+///
+/// Each variant correspond to a `listen_type` of the API.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SubmitListensPayload {
+    /// Submit a single listen. It must have a `listened_at` timestamp
+    Single(SubmittedListen),
+
+    /// Submit multiple listens at once. They must all have a `listened_at` timestamp
+    Import(Vec<SubmittedListen>),
+
+    /// Set the track currently playing. It must **not** have a `listened_at` timestamp
+    PlayingNow(SubmittedListen),
+}
+
+impl SubmitListensPayload {
+    /// The value of the `listen_type` field
+    pub fn listen_type(&self) -> &'static str {
+        match self {
+            Self::Single(_) => "single",
+            Self::Import(_) => "import",
+            Self::PlayingNow(_) => "playing_now",
+        }
+    }
+
+    /// The listens contained in the payload
+    pub fn listens(&self) -> &[SubmittedListen] {
+        match self {
+            Self::Single(listen) | Self::PlayingNow(listen) => core::slice::from_ref(listen),
+            Self::Import(listens) => listens,
+        }
+    }
+}
+
+impl Serialize for SubmitListensPayload {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut state = serializer.serialize_struct("SubmitListensPayload", 2)?;
+        state.serialize_field("listen_type", self.listen_type())?;
+        state.serialize_field("payload", self.listens())?;
+        state.end()
+    }
+}
+
+/// A listen to submit. Type of the [`SubmitListensPayload`] listens
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, bon::Builder)]
+pub struct SubmittedListen {
+    /// The timestamp of when the track was listened to. Must be `None` for [`SubmitListensPayload::PlayingNow`]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub listened_at: Option<i64>,
+
+    pub track_metadata: SubmittedTrackMetadata,
+}
+
+/// Type of the [`SubmittedListen::track_metadata`] field.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, bon::Builder)]
+pub struct SubmittedTrackMetadata {
+    #[builder(into)]
+    pub artist_name: String,
+
+    #[builder(into)]
+    pub track_name: String,
+
+    #[builder(into)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub release_name: Option<String>,
+
+    /// Extra data about the listen, like MBIDs, tags, or the music service used.
+    ///
+    /// See the [official documentation](https://listenbrainz.readthedocs.io/en/latest/users/json.html#payload-json-details) for the known keys
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub additional_info: Option<HashMap<String, serde_json::Value>>,
+}
+
+// === Response types ===
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct SubmitListensResponse {
+    pub status: String,
+}
+
+#[derive(Debug, Snafu)]
+pub enum SubmitListensError {
+    RequestBuildingError {
+        source: RequestBuildingError,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+
+    #[snafu(display("The listen payload is invalid"))]
+    ValidationError {
+        source: ListenValidationError,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+}
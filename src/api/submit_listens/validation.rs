@@ -0,0 +1,412 @@
+use snafu::ResultExt as _;
+use snafu::Snafu;
+use snafu::ensure;
+
+use crate::api::submit_listens::SubmitListensPayload;
+use crate::api::submit_listens::SubmittedListen;
+use crate::models::mbid::is_valid_mbid;
+
+/// The maximum number of listens in a single request
+pub const MAX_LISTENS_PER_REQUEST: usize = 1000;
+
+/// The maximum size of a single listen, in bytes
+pub const MAX_LISTEN_SIZE: usize = 10240;
+
+/// The maximum size of the whole payload of a request, in bytes
+pub const MAX_LISTEN_PAYLOAD_SIZE: usize = 10_240_000;
+
+/// The maximum number of tags in `additional_info.tags`
+pub const MAX_TAGS_PER_LISTEN: usize = 50;
+
+/// The maximum number of characters of a tag
+pub const MAX_TAG_SIZE: usize = 64;
+
+/// The oldest `listened_at` timestamp accepted by the server
+pub const LISTEN_MINIMUM_TS: i64 = 1_033_430_400;
+
+/// The `additional_info` keys that must contain a single MBID
+const SINGLE_MBID_KEYS: [&str; 4] = [
+    "release_mbid",
+    "recording_mbid",
+    "release_group_mbid",
+    "track_mbid",
+];
+
+/// The `additional_info` keys that must contain a list of MBIDs
+const MULTIPLE_MBID_KEYS: [&str; 2] = ["artist_mbids", "work_mbids"];
+
+impl SubmitListensPayload {
+    /// Check the payload against the rules of the server.
+    pub fn validate(&self) -> Result<(), ListenValidationError> {
+        let listens = self.listens();
+
+        ensure!(!listens.is_empty(), EmptyPayloadSnafu);
+        ensure!(
+            listens.len() <= MAX_LISTENS_PER_REQUEST,
+            TooManyListensSnafu {
+                count: listens.len()
+            }
+        );
+
+        let is_playing_now = matches!(self, Self::PlayingNow(_));
+        for (index, listen) in listens.iter().enumerate() {
+            listen
+                .validate(is_playing_now)
+                .context(InvalidListenSnafu { index })?;
+        }
+
+        let size = serde_json::to_vec(self)
+            .context(PayloadSerializationSnafu)?
+            .len();
+        ensure!(
+            size <= MAX_LISTEN_PAYLOAD_SIZE,
+            PayloadTooLargeSnafu { size }
+        );
+
+        Ok(())
+    }
+}
+
+impl SubmittedListen {
+    /// Check the listen against the rules of the server.
+    ///
+    /// `is_playing_now` must be set to true if the listen is sent as a `playing_now` listen
+    pub fn validate(&self, is_playing_now: bool) -> Result<(), InvalidListenError> {
+        match (is_playing_now, self.listened_at) {
+            (true, Some(_)) => return UnexpectedListenedAtSnafu.fail(),
+            (false, None) => return MissingListenedAtSnafu.fail(),
+            (false, Some(listened_at)) => ensure!(
+                listened_at >= LISTEN_MINIMUM_TS,
+                ListenedAtTooOldSnafu { listened_at }
+            ),
+            (true, None) => {}
+        }
+
+        let metadata = &self.track_metadata;
+        ensure!(
+            !metadata.artist_name.trim().is_empty(),
+            EmptyFieldSnafu {
+                field: "artist_name"
+            }
+        );
+        ensure!(
+            !metadata.track_name.trim().is_empty(),
+            EmptyFieldSnafu {
+                field: "track_name"
+            }
+        );
+
+        if let Some(additional_info) = &metadata.additional_info {
+            for (key, value) in additional_info {
+                validate_additional_info_entry(key, value)?;
+            }
+        }
+
+        let size = serde_json::to_vec(self)
+            .context(ListenSerializationSnafu)?
+            .len();
+        ensure!(size <= MAX_LISTEN_SIZE, ListenTooLargeSnafu { size });
+
+        Ok(())
+    }
+}
+
+fn validate_additional_info_entry(
+    key: &str,
+    value: &serde_json::Value,
+) -> Result<(), InvalidListenError> {
+    ensure!(!key.trim().is_empty(), EmptyAdditionalInfoKeySnafu);
+
+    if key == "tags" {
+        let tags = value.as_array().ok_or_else(|| {
+            InvalidAdditionalInfoSnafu {
+                key,
+                reason: "must be a list of strings",
+            }
+            .build()
+        })?;
+
+        ensure!(
+            tags.len() <= MAX_TAGS_PER_LISTEN,
+            InvalidAdditionalInfoSnafu {
+                key,
+                reason: format!("can't have more than {MAX_TAGS_PER_LISTEN} tags"),
+            }
+        );
+
+        for tag in tags {
+            let valid = tag
+                .as_str()
+                .is_some_and(|tag| tag.chars().count() <= MAX_TAG_SIZE);
+            ensure!(
+                valid,
+                InvalidAdditionalInfoSnafu {
+                    key,
+                    reason: format!("tags must be strings of at most {MAX_TAG_SIZE} characters"),
+                }
+            );
+        }
+    }
+
+    if SINGLE_MBID_KEYS.contains(&key) {
+        ensure!(
+            value.as_str().is_some_and(is_valid_mbid),
+            InvalidAdditionalInfoSnafu {
+                key,
+                reason: "must be a valid MBID",
+            }
+        );
+    }
+
+    if MULTIPLE_MBID_KEYS.contains(&key) {
+        let valid = value.as_array().is_some_and(|mbids| {
+            mbids
+                .iter()
+                .all(|mbid| mbid.as_str().is_some_and(is_valid_mbid))
+        });
+        ensure!(
+            valid,
+            InvalidAdditionalInfoSnafu {
+                key,
+                reason: "must be a list of valid MBIDs",
+            }
+        );
+    }
+
+    if key == "duration" || key == "duration_ms" {
+        ensure!(
+            value.as_u64().is_some_and(|duration| duration > 0),
+            InvalidAdditionalInfoSnafu {
+                key,
+                reason: "must be a positive integer",
+            }
+        );
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Snafu)]
+pub enum ListenValidationError {
+    #[snafu(display("The payload doesn't contain any listens"))]
+    EmptyPayload {
+        #[snafu(implicit)]
+        location: snafu::Location,
+    },
+
+    #[snafu(display(
+        "The payload contains {count} listens, but the maximum is {MAX_LISTENS_PER_REQUEST}"
+    ))]
+    TooManyListens {
+        count: usize,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+    },
+
+    #[snafu(display(
+        "The payload is {size} bytes long, but the maximum is {MAX_LISTEN_PAYLOAD_SIZE}"
+    ))]
+    PayloadTooLarge {
+        size: usize,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+    },
+
+    #[snafu(display("The payload couldn't be serialized to check its size"))]
+    PayloadSerializationError {
+        source: serde_json::Error,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+    },
+
+    #[snafu(display("The listen at index {index} is invalid"))]
+    InvalidListen {
+        index: usize,
+        source: InvalidListenError,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+    },
+}
+
+#[derive(Debug, Snafu)]
+pub enum InvalidListenError {
+    #[snafu(display("The listen is missing its `listened_at` timestamp"))]
+    MissingListenedAt {
+        #[snafu(implicit)]
+        location: snafu::Location,
+    },
+
+    #[snafu(display("A `playing_now` listen must not have a `listened_at` timestamp"))]
+    UnexpectedListenedAt {
+        #[snafu(implicit)]
+        location: snafu::Location,
+    },
+
+    #[snafu(display(
+        "The `listened_at` timestamp {listened_at} is older than the minimum of {LISTEN_MINIMUM_TS}"
+    ))]
+    ListenedAtTooOld {
+        listened_at: i64,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+    },
+
+    #[snafu(display("The field `{field}` must not be empty"))]
+    EmptyField {
+        field: &'static str,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+    },
+
+    #[snafu(display("`additional_info` contains an empty key"))]
+    EmptyAdditionalInfoKey {
+        #[snafu(implicit)]
+        location: snafu::Location,
+    },
+
+    #[snafu(display("`additional_info.{key}` {reason}"))]
+    InvalidAdditionalInfo {
+        key: String,
+        reason: String,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+    },
+
+    #[snafu(display("The listen couldn't be serialized to check its size"))]
+    ListenSerializationError {
+        source: serde_json::Error,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+    },
+
+    #[snafu(display("The listen is {size} bytes long, but the maximum is {MAX_LISTEN_SIZE}"))]
+    ListenTooLarge {
+        size: usize,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+    },
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use crate::api::submit_listens::SubmitListensPayload;
+    use crate::api::submit_listens::SubmittedListen;
+    use crate::api::submit_listens::SubmittedTrackMetadata;
+    use crate::api::submit_listens::validation::MAX_LISTENS_PER_REQUEST;
+
+    fn listen(listened_at: Option<i64>) -> SubmittedListen {
+        SubmittedListen::builder()
+            .maybe_listened_at(listened_at)
+            .track_metadata(
+                SubmittedTrackMetadata::builder()
+                    .artist_name("Kikuo")
+                    .track_name("Aishite Aishite Aishite")
+                    .build(),
+            )
+            .build()
+    }
+
+    #[test]
+    fn validate_listened_at_test() {
+        assert!(
+            SubmitListensPayload::Single(listen(Some(1_705_000_000)))
+                .validate()
+                .is_ok()
+        );
+        assert!(
+            SubmitListensPayload::Single(listen(None))
+                .validate()
+                .is_err()
+        );
+        assert!(
+            SubmitListensPayload::Single(listen(Some(1_000)))
+                .validate()
+                .is_err()
+        );
+
+        assert!(
+            SubmitListensPayload::PlayingNow(listen(None))
+                .validate()
+                .is_ok()
+        );
+        assert!(
+            SubmitListensPayload::PlayingNow(listen(Some(1_705_000_000)))
+                .validate()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn validate_import_size_test() {
+        assert!(SubmitListensPayload::Import(Vec::new()).validate().is_err());
+
+        let listens = vec![listen(Some(1_705_000_000)); MAX_LISTENS_PER_REQUEST];
+        assert!(
+            SubmitListensPayload::Import(listens.clone())
+                .validate()
+                .is_ok()
+        );
+
+        let mut listens = listens;
+        listens.push(listen(Some(1_705_000_000)));
+        assert!(SubmitListensPayload::Import(listens).validate().is_err());
+    }
+
+    #[test]
+    fn validate_additional_info_test() {
+        let with_info = |key: &str, value: serde_json::Value| {
+            let mut listen = listen(Some(1_705_000_000));
+            listen.track_metadata.additional_info = Some(HashMap::from([(key.to_string(), value)]));
+            SubmitListensPayload::Single(listen).validate()
+        };
+
+        assert!(
+            with_info(
+                "recording_mbid",
+                "61c54b0e-3a82-49af-9cc7-73ff34365697".into()
+            )
+            .is_ok()
+        );
+        assert!(with_info("recording_mbid", "not an mbid".into()).is_err());
+        assert!(
+            with_info(
+                "artist_mbids",
+                serde_json::json!(["61c54b0e-3a82-49af-9cc7-73ff34365697"])
+            )
+            .is_ok()
+        );
+        assert!(with_info("tags", serde_json::json!(["vocaloid"])).is_ok());
+        assert!(with_info("tags", serde_json::json!(["a".repeat(65)])).is_err());
+        assert!(with_info("duration_ms", serde_json::json!(0)).is_err());
+        assert!(with_info("", serde_json::json!("value")).is_err());
+    }
+
+    #[test]
+    fn serialize_payload_test() {
+        let payload = SubmitListensPayload::PlayingNow(listen(None));
+
+        assert_eq!(
+            serde_json::to_value(&payload).unwrap(),
+            serde_json::json!({
+                "listen_type": "playing_now",
+                "payload": [{
+                    "track_metadata": {
+                        "artist_name": "Kikuo",
+                        "track_name": "Aishite Aishite Aishite"
+                    }
+                }]
+            })
+        );
+    }
+}
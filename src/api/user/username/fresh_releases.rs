@@ -2,12 +2,15 @@ use core::fmt::Display;
 
 use api_bindium::ApiRequest;
 use api_bindium::HTTPVerb;
-use api_bindium::JsonParser;
 use api_bindium::endpoints::UriBuilderError;
 use serde::Deserialize;
 use serde::Serialize;
 
 use crate::api::ListenBrainzAPIEnpoints;
+use crate::models::mbid::ArtistMbid;
+use crate::models::mbid::ReleaseGroupMbid;
+use crate::models::mbid::ReleaseMbid;
+use crate::parser::ListenBrainzParser;
 
 #[bon::bon]
 impl ListenBrainzAPIEnpoints {
@@ -23,14 +26,14 @@ impl ListenBrainzAPIEnpoints {
         future: Option<bool>,
         /// The number of days of fresh releases to show.
         days: Option<u64>,
-    ) -> Result<ApiRequest<JsonParser<FreshReleaseResponse>>, UriBuilderError> {
+    ) -> Result<ApiRequest<ListenBrainzParser<FreshReleaseResponse>>, UriBuilderError> {
         self.endpoint_builder()
             .set_path(&format!("/1/user/{username}/fresh_releases"))
             .maybe_add_parameter("sort", sort)
             .maybe_add_parameter("past", past)
             .maybe_add_parameter("future", future)
             .maybe_add_parameter("days", days)
-            .into_api_request(HTTPVerb::Get, JsonParser::default())
+            .into_api_request(HTTPVerb::Get, ListenBrainzParser::default())
     }
 }
 
@@ -73,16 +76,16 @@ pub struct FreshReleasePayload {
 #[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
 pub struct FreshReleaseRelease {
     pub artist_credit_name: String,
-    pub artist_mbids: Vec<String>,
+    pub artist_mbids: Vec<ArtistMbid>,
     pub caa_id: Option<u64>,
-    pub caa_release_mbid: Option<String>,
+    pub caa_release_mbid: Option<ReleaseMbid>,
     pub confidence: u64,
     pub listen_count: u64,
     pub release_date: String,
-    pub release_group_mbid: String,
+    pub release_group_mbid: ReleaseGroupMbid,
     pub release_group_primary_type: Option<String>,
     pub release_group_secondary_type: Option<String>,
-    pub release_mbid: String,
+    pub release_mbid: ReleaseMbid,
     pub release_name: String,
     pub release_tags: Vec<String>,
 }
@@ -90,24 +93,81 @@ pub struct FreshReleaseRelease {
 #[cfg(test)]
 #[cfg(feature = "async")]
 mod test {
-
     use macro_rules_attribute::apply;
 
-    use crate::client::ListenBrainzClient;
+    use crate::client::cassette::fixture_cassette_client;
+    use crate::error::ErrorCategory;
+    use crate::mock_server::MockListenBrainzServer;
+    use crate::mock_server::data::FIXTURE_FRESH_RELEASE_ARTIST_MBID;
+    use crate::mock_server::data::FIXTURE_FRESH_RELEASE_GROUP_MBID;
+    use crate::mock_server::data::FIXTURE_FRESH_RELEASE_MBID;
+    use crate::mock_server::data::FIXTURE_USER;
 
     #[apply(smol_macros::test!)]
-
     async fn get_user_username_fresh_releases_test() {
-        let client = ListenBrainzClient::default();
+        let server = MockListenBrainzServer::start_with_fixtures().unwrap();
+        let client = server.client();
 
-        let mut req = client
-            .endpoints()
-            .get_user_username_fresh_releases()
-            .username("RustyNova")
+        let res = client
+            .get_user_username_fresh_releases_async()
+            .username(FIXTURE_USER)
             .call()
+            .await
             .unwrap();
-        let _res = req.send_async(client.api_client()).await.unwrap();
 
-        // Can't do much here as fresh releases are dynamic...
+        let [release] = res.payload.releases.as_slice() else {
+            panic!("Expected a single fresh release");
+        };
+        assert_eq!(release.release_mbid.as_str(), FIXTURE_FRESH_RELEASE_MBID);
+        assert_eq!(
+            release.caa_release_mbid.as_ref().map(|mbid| mbid.as_str()),
+            Some(FIXTURE_FRESH_RELEASE_MBID)
+        );
+        assert_eq!(
+            release.release_group_mbid.as_str(),
+            FIXTURE_FRESH_RELEASE_GROUP_MBID
+        );
+        assert_eq!(
+            release
+                .artist_mbids
+                .iter()
+                .map(|mbid| mbid.as_str())
+                .collect::<Vec<_>>(),
+            [FIXTURE_FRESH_RELEASE_ARTIST_MBID]
+        );
+    }
+
+    #[apply(smol_macros::test!)]
+    async fn invalid_fresh_release_mbid_test() {
+        let server = MockListenBrainzServer::start_with_fixtures().unwrap();
+        let client = server.client();
+
+        server
+            .data()
+            .users
+            .get_mut(FIXTURE_USER)
+            .unwrap()
+            .fresh_releases[0]["release_group_mbid"] = "not-an-mbid".into();
+
+        let err = client
+            .get_user_username_fresh_releases_async()
+            .username(FIXTURE_USER)
+            .call()
+            .await
+            .unwrap_err();
+        assert_eq!(err.category(), ErrorCategory::JsonDecoding);
+    }
+
+    #[apply(smol_macros::test!)]
+    async fn get_user_username_fresh_releases_cassette_test() {
+        let client = fixture_cassette_client("fresh_releases");
+
+        // The fresh releases change every day, so this only checks that the recorded ones can be decoded
+        client
+            .get_user_username_fresh_releases_async()
+            .username(FIXTURE_USER)
+            .call()
+            .await
+            .unwrap();
     }
 }
@@ -0,0 +1,383 @@
+use api_bindium::ApiRequest;
+use api_bindium::ApiRequestError;
+use api_bindium::HTTPVerb;
+use api_bindium::endpoints::UriBuilderError;
+use serde::Deserialize;
+use serde::Serialize;
+use snafu::Snafu;
+
+use crate::api::ListenBrainzAPIEnpoints;
+use crate::parser::ListenBrainzParser;
+use crate::parser::ResponseError;
+
+impl ListenBrainzAPIEnpoints {
+    /// Get the total number of listens of the user
+    pub fn get_user_username_listen_count(
+        &self,
+        username: &str,
+    ) -> Result<ApiRequest<ListenBrainzParser<UserListenCountResponse>>, UriBuilderError> {
+        self.endpoint_builder()
+            .set_path(&format!("/1/user/{username}/listen-count"))
+            .into_api_request(HTTPVerb::Get, ListenBrainzParser::default())
+    }
+}
+
+#[cfg(feature = "async")]
+#[bon::bon]
+impl ListenBrainzAPIEnpoints {
+    /// Take a [`ListensSnapshot`] of the user's listens.
+    ///
+    /// This costs two small requests, no matter how many listens the user has.
+    #[builder]
+    pub async fn get_listens_snapshot(
+        client: &crate::ListenBrainzClient,
+        username: &str,
+    ) -> Result<ListensSnapshot, ListensSnapshotError> {
+        let listen_count = fetch_listen_count(client, username).await?;
+        let latest_listen_ts = fetch_latest_listen_ts(client, username).await?;
+
+        Ok(ListensSnapshot {
+            listen_count,
+            latest_listen_ts,
+        })
+    }
+
+    /// Check if the listens of the user have changed since the `previous` snapshot was taken.
+    ///
+    /// The listen count is checked first, and the latest listen timestamp is only fetched if the counts are the same,
+    /// so this costs one or two small requests. If this returns `false`, there's no need to refetch the listens.
+    #[builder]
+    pub async fn have_listens_changed(
+        client: &crate::ListenBrainzClient,
+        username: &str,
+        previous: &ListensSnapshot,
+    ) -> Result<bool, ListensSnapshotError> {
+        if fetch_listen_count(client, username).await? != previous.listen_count {
+            return Ok(true);
+        }
+
+        Ok(fetch_latest_listen_ts(client, username).await? != previous.latest_listen_ts)
+    }
+}
+
+#[cfg(feature = "sync")]
+#[bon::bon]
+impl ListenBrainzAPIEnpoints {
+    /// Take a [`ListensSnapshot`] of the user's listens. This is the blocking version of [`Self::get_listens_snapshot`]
+    ///
+    /// This costs two small requests, no matter how many listens the user has.
+    #[builder]
+    pub fn get_listens_snapshot_blocking(
+        client: &crate::ListenBrainzClient,
+        username: &str,
+    ) -> Result<ListensSnapshot, ListensSnapshotError> {
+        Ok(ListensSnapshot {
+            listen_count: fetch_listen_count_blocking(client, username)?,
+            latest_listen_ts: fetch_latest_listen_ts_blocking(client, username)?,
+        })
+    }
+
+    /// Check if the listens of the user have changed since the `previous` snapshot was taken.
+    /// This is the blocking version of [`Self::have_listens_changed`]
+    ///
+    /// The listen count is checked first, and the latest listen timestamp is only fetched if the counts are the same,
+    /// so this costs one or two small requests. If this returns `false`, there's no need to refetch the listens.
+    #[builder]
+    pub fn have_listens_changed_blocking(
+        client: &crate::ListenBrainzClient,
+        username: &str,
+        previous: &ListensSnapshot,
+    ) -> Result<bool, ListensSnapshotError> {
+        if fetch_listen_count_blocking(client, username)? != previous.listen_count {
+            return Ok(true);
+        }
+
+        Ok(fetch_latest_listen_ts_blocking(client, username)? != previous.latest_listen_ts)
+    }
+}
+
+// The snapshot requests always ask the server: a cached answer would hide the changes they are meant to detect
+
+#[cfg(feature = "async")]
+pub(crate) async fn fetch_listen_count(
+    client: &crate::ListenBrainzClient,
+    username: &str,
+) -> Result<u64, ListensSnapshotError> {
+    use snafu::ResultExt as _;
+
+    let res = client
+        .bypassing_cache()
+        .send_async(&mut listen_count_request(client, username)?)
+        .await
+        .context(ApiRequestSnafu)?
+        .parse()
+        .context(ResponseSnafu)?;
+
+    Ok(res.payload.count)
+}
+
+#[cfg(feature = "sync")]
+pub(crate) fn fetch_listen_count_blocking(
+    client: &crate::ListenBrainzClient,
+    username: &str,
+) -> Result<u64, ListensSnapshotError> {
+    use snafu::ResultExt as _;
+
+    let res = client
+        .bypassing_cache()
+        .send(&mut listen_count_request(client, username)?)
+        .context(ApiRequestSnafu)?
+        .parse()
+        .context(ResponseSnafu)?;
+
+    Ok(res.payload.count)
+}
+
+#[cfg(feature = "async")]
+async fn fetch_latest_listen_ts(
+    client: &crate::ListenBrainzClient,
+    username: &str,
+) -> Result<i64, ListensSnapshotError> {
+    use snafu::ResultExt as _;
+
+    let res = client
+        .bypassing_cache()
+        .send_async(&mut latest_listen_request(client, username)?)
+        .await
+        .context(ApiRequestSnafu)?
+        .parse()
+        .context(ResponseSnafu)?;
+
+    Ok(res.payload.latest_listen_ts)
+}
+
+#[cfg(feature = "sync")]
+fn fetch_latest_listen_ts_blocking(
+    client: &crate::ListenBrainzClient,
+    username: &str,
+) -> Result<i64, ListensSnapshotError> {
+    use snafu::ResultExt as _;
+
+    let res = client
+        .bypassing_cache()
+        .send(&mut latest_listen_request(client, username)?)
+        .context(ApiRequestSnafu)?
+        .parse()
+        .context(ResponseSnafu)?;
+
+    Ok(res.payload.latest_listen_ts)
+}
+
+#[cfg(any(feature = "sync", feature = "async"))]
+fn listen_count_request(
+    client: &crate::ListenBrainzClient,
+    username: &str,
+) -> Result<ApiRequest<ListenBrainzParser<UserListenCountResponse>>, ListensSnapshotError> {
+    use snafu::ResultExt as _;
+
+    client
+        .endpoints()
+        .get_user_username_listen_count(username)
+        .context(UriBuilderSnafu)
+}
+
+#[cfg(any(feature = "sync", feature = "async"))]
+fn latest_listen_request(
+    client: &crate::ListenBrainzClient,
+    username: &str,
+) -> Result<
+    ApiRequest<ListenBrainzParser<crate::api::user::username::listens::UserListensResponse>>,
+    ListensSnapshotError,
+> {
+    use snafu::ResultExt as _;
+
+    client
+        .endpoints()
+        .get_user_username_listens()
+        .username(username)
+        .count(1)
+        .call()
+        .context(UriBuilderSnafu)
+}
+
+// === Response types ===
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct UserListenCountResponse {
+    pub payload: UserListenCountPayload,
+}
+
+/// Type of the [`UserListenCountResponse::payload`] field.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct UserListenCountPayload {
+    pub count: u64,
+}
+
+/// A cheap summary of the state of a user's listens, used to detect if anything changed between two syncs
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct ListensSnapshot {
+    /// The total number of listens of the user
+    pub listen_count: u64,
+
+    /// The timestamp of the latest listen of the user
+    pub latest_listen_ts: i64,
+}
+
+#[derive(Debug, Snafu)]
+pub enum ListensSnapshotError {
+    UriBuilderError {
+        source: UriBuilderError,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+
+    ApiRequestError {
+        source: ApiRequestError,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+
+    ResponseError {
+        source: ResponseError,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+}
+
+#[cfg(test)]
+#[cfg(feature = "async")]
+mod test {
+
+    use macro_rules_attribute::apply;
+
+    use crate::api::ListenBrainzAPIEnpoints;
+    use crate::client::cache::ResponseCache;
+    use crate::mock_server::MockListenBrainzServer;
+    use crate::mock_server::data::FIXTURE_USER;
+
+    #[apply(smol_macros::test!)]
+
+    async fn have_listens_changed_test() {
+        let server = MockListenBrainzServer::start_with_fixtures().unwrap();
+        let client = server.client();
+
+        let snapshot = ListenBrainzAPIEnpoints::get_listens_snapshot()
+            .client(&client)
+            .username("RustyNova")
+            .call()
+            .await
+            .unwrap();
+
+        assert!(snapshot.listen_count > 0);
+
+        let changed = ListenBrainzAPIEnpoints::have_listens_changed()
+            .client(&client)
+            .username("RustyNova")
+            .previous(&snapshot)
+            .call()
+            .await
+            .unwrap();
+
+        assert!(!changed);
+
+        let mut outdated = snapshot.clone();
+        outdated.listen_count -= 1;
+        let changed = ListenBrainzAPIEnpoints::have_listens_changed()
+            .client(&client)
+            .username("RustyNova")
+            .previous(&outdated)
+            .call()
+            .await
+            .unwrap();
+
+        assert!(changed);
+    }
+
+    #[apply(smol_macros::test!)]
+    async fn have_listens_changed_cached_test() {
+        let server = MockListenBrainzServer::start_with_fixtures().unwrap();
+        let client = crate::ListenBrainzClient::builder()
+            .endpoints(server.endpoints())
+            .cache(ResponseCache::builder().build())
+            .build();
+
+        let snapshot = ListenBrainzAPIEnpoints::get_listens_snapshot()
+            .client(&client)
+            .username(FIXTURE_USER)
+            .call()
+            .await
+            .unwrap();
+
+        add_newer_listen(&server);
+
+        let changed = ListenBrainzAPIEnpoints::have_listens_changed()
+            .client(&client)
+            .username(FIXTURE_USER)
+            .previous(&snapshot)
+            .call()
+            .await
+            .unwrap();
+
+        assert!(changed);
+    }
+
+    #[cfg(feature = "sync")]
+    #[test]
+    fn have_listens_changed_blocking_test() {
+        let server = MockListenBrainzServer::start_with_fixtures().unwrap();
+        let client = crate::ListenBrainzClient::builder()
+            .endpoints(server.endpoints())
+            .cache(ResponseCache::builder().build())
+            .build();
+
+        let snapshot = ListenBrainzAPIEnpoints::get_listens_snapshot_blocking()
+            .client(&client)
+            .username(FIXTURE_USER)
+            .call()
+            .unwrap();
+
+        let changed = ListenBrainzAPIEnpoints::have_listens_changed_blocking()
+            .client(&client)
+            .username(FIXTURE_USER)
+            .previous(&snapshot)
+            .call()
+            .unwrap();
+        assert!(!changed);
+
+        add_newer_listen(&server);
+
+        let changed = ListenBrainzAPIEnpoints::have_listens_changed_blocking()
+            .client(&client)
+            .username(FIXTURE_USER)
+            .previous(&snapshot)
+            .call()
+            .unwrap();
+        assert!(changed);
+    }
+
+    /// Listen to the latest track of the fixture user again, one second later
+    fn add_newer_listen(server: &MockListenBrainzServer) {
+        let mut data = server.data();
+        let user = data.users.get_mut(FIXTURE_USER).unwrap();
+        let mut listen = user
+            .listens
+            .iter()
+            .max_by_key(|listen| listen.listened_at)
+            .unwrap()
+            .clone();
+        listen.listened_at += 1;
+        user.listens.push(listen);
+    }
+}
@@ -2,12 +2,19 @@ use std::collections::HashMap;
 
 use api_bindium::ApiRequest;
 use api_bindium::HTTPVerb;
-use api_bindium::JsonParser;
 use api_bindium::endpoints::UriBuilderError;
+use chrono::DateTime;
+use chrono::Utc;
 use serde::Deserialize;
 use serde::Serialize;
 
 use crate::api::ListenBrainzAPIEnpoints;
+use crate::models::mbid::ArtistMbid;
+use crate::models::mbid::RecordingMbid;
+use crate::models::mbid::RecordingMsid;
+use crate::models::mbid::ReleaseMbid;
+use crate::models::timestamp::Timestamp;
+use crate::parser::ListenBrainzParser;
 
 #[bon::bon]
 impl ListenBrainzAPIEnpoints {
@@ -15,16 +22,20 @@ impl ListenBrainzAPIEnpoints {
     pub fn get_user_username_listens(
         &self,
         username: &str,
-        max_ts: Option<u64>,
-        min_ts: Option<u64>,
+        /// Only return listens listened before this time
+        #[builder(into)]
+        max_ts: Option<Timestamp>,
+        /// Only return listens listened after this time
+        #[builder(into)]
+        min_ts: Option<Timestamp>,
         count: Option<u64>,
-    ) -> Result<ApiRequest<JsonParser<UserListensResponse>>, UriBuilderError> {
+    ) -> Result<ApiRequest<ListenBrainzParser<UserListensResponse>>, UriBuilderError> {
         self.endpoint_builder()
             .set_path(&format!("/1/user/{username}/listens"))
             .maybe_add_parameter("max_ts", max_ts)
             .maybe_add_parameter("min_ts", min_ts)
             .maybe_add_parameter("count", count)
-            .into_api_request(HTTPVerb::Get, JsonParser::default())
+            .into_api_request(HTTPVerb::Get, ListenBrainzParser::default())
     }
 }
 
@@ -43,22 +54,47 @@ pub struct UserListensPayload {
     pub listens: Vec<UserListensListen>,
 }
 
+impl UserListensPayload {
+    /// The time of the latest listen of the user
+    pub fn latest_listen_datetime(&self) -> Option<DateTime<Utc>> {
+        Timestamp::from_secs(self.latest_listen_ts).to_datetime()
+    }
+
+    /// The time of the oldest listen of the user
+    pub fn oldest_listen_datetime(&self) -> Option<DateTime<Utc>> {
+        Timestamp::from_secs(self.oldest_listen_ts).to_datetime()
+    }
+}
+
 /// Type of the [`UserListensPayload::listens`] field.
 #[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
 pub struct UserListensListen {
     pub user_name: String,
     pub inserted_at: i64,
     pub listened_at: i64,
-    pub recording_msid: String,
+    pub recording_msid: RecordingMsid,
     pub track_metadata: UserListensTrackMetadata,
 }
 
+impl UserListensListen {
+    /// The time the listen got listened at
+    pub fn listened_at_datetime(&self) -> Option<DateTime<Utc>> {
+        Timestamp::from_secs(self.listened_at).to_datetime()
+    }
+
+    /// The time the listen got inserted in the database
+    pub fn inserted_at_datetime(&self) -> Option<DateTime<Utc>> {
+        Timestamp::from_secs(self.inserted_at).to_datetime()
+    }
+}
+
 /// Type of the [`UserListensListen::track_metadata`] field.
 #[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
 pub struct UserListensTrackMetadata {
     pub artist_name: String,
     pub track_name: String,
     pub release_name: Option<String>,
+    #[serde(default)]
     pub additional_info: HashMap<String, serde_json::Value>,
     pub mbid_mapping: Option<UserListensMBIDMapping>,
 }
@@ -66,19 +102,19 @@ pub struct UserListensTrackMetadata {
 /// Type of the [`UserListensTrackMetadata::mbid_mapping`] field.
 #[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
 pub struct UserListensMBIDMapping {
-    pub artist_mbids: Option<Vec<String>>,
+    pub artist_mbids: Option<Vec<ArtistMbid>>,
     pub artists: Option<Vec<UserListensMappingArtist>>,
-    pub recording_mbid: String,
+    pub recording_mbid: RecordingMbid,
     pub recording_name: Option<String>,
     pub caa_id: Option<u64>,
-    pub caa_release_mbid: Option<String>,
-    pub release_mbid: Option<String>,
+    pub caa_release_mbid: Option<ReleaseMbid>,
+    pub release_mbid: Option<ReleaseMbid>,
 }
 
 /// Type of the [`UserListensMBIDMapping::artists`] field.
 #[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
 pub struct UserListensMappingArtist {
-    pub artist_mbid: String,
+    pub artist_mbid: ArtistMbid,
     pub artist_credit_name: String,
     pub join_phrase: String,
 }
@@ -89,33 +125,53 @@ mod test {
 
     use macro_rules_attribute::apply;
 
-    use crate::client::ListenBrainzClient;
+    use crate::client::cassette::fixture_cassette_client;
+    use crate::mock_server::MockListenBrainzServer;
 
     #[apply(smol_macros::test!)]
 
     async fn get_user_username_listens_test() {
-        let client = ListenBrainzClient::default();
+        let server = MockListenBrainzServer::start_with_fixtures().unwrap();
+        let client = server.client();
 
-        let mut req = client
-            .endpoints()
-            .get_user_username_listens()
+        let mut res = client
+            .get_user_username_listens_async()
             .username("RustyNova")
-            .min_ts(1763396995)
-            .max_ts(1763396997)
+            .min_ts(1_763_396_995_i64)
+            .max_ts(1_763_396_997_i64)
             .count(1)
             .call()
-            .unwrap();
-        let mut res = req
-            .send_async(client.api_client())
             .await
-            .unwrap()
-            .parse()
             .unwrap();
 
         assert_eq!(res.payload.count, 1);
 
         let first = res.payload.listens.pop().unwrap();
 
-        assert_eq!(first.recording_msid, "cfb002e7-f093-4678-8bf7-fb139a4f718c")
+        assert_eq!(
+            first.recording_msid.as_str(),
+            "cfb002e7-f093-4678-8bf7-fb139a4f718c"
+        )
+    }
+
+    #[apply(smol_macros::test!)]
+    async fn get_user_username_listens_cassette_test() {
+        let client = fixture_cassette_client("listens");
+
+        let mut res = client
+            .get_user_username_listens_async()
+            .username("RustyNova")
+            .min_ts(1_763_396_995_i64)
+            .max_ts(1_763_396_997_i64)
+            .count(1)
+            .call()
+            .await
+            .unwrap();
+
+        assert_eq!(res.payload.count, 1);
+        assert_eq!(
+            res.payload.listens.pop().unwrap().recording_msid.as_str(),
+            "cfb002e7-f093-4678-8bf7-fb139a4f718c"
+        );
     }
 }
@@ -0,0 +1,157 @@
+use snafu::ResultExt as _;
+
+use crate::api::ListenBrainzAPIEnpoints;
+use crate::api::user::username::listens::UserListensListen;
+use crate::api::user::username::listens::UserListensResponse;
+use crate::api::user::username::listens_reader::ApiRequestSnafu;
+use crate::api::user::username::listens_reader::ListenFullFetchError;
+use crate::api::user::username::listens_reader::ParserSnafu;
+use crate::api::user::username::listens_reader::UserListensFullResult;
+use crate::api::user::username::listens_reader::checkpoint::ListenFetchCheckpoint;
+use crate::api::user::username::listens_reader::finish_fetch;
+use crate::api::user::username::listens_reader::interrupt;
+use crate::api::user::username::listens_reader::planner::ListenWindowPlanner;
+use crate::api::user::username::listens_reader::window_request;
+use crate::client::ListenBrainzClient;
+use crate::models::timestamp::Timestamp;
+
+#[bon::bon]
+impl ListenBrainzAPIEnpoints {
+    /// Get all the listens in a time period, removing the paging. This is the blocking version of [`Self::get_user_username_listens_full`]
+    ///
+    /// The size of the fetched time windows adapts to the listen density of the user, so light listeners only need a few requests,
+    /// while heavy listeners don't get overflowing windows.
+    ///
+    /// Due to implementation details and quirks in the API, the listens may not be sorted and may contain duplicates.
+    /// Set `sorted` to get the listens from newest to oldest, without duplicates.
+    ///
+    /// If a request fails, a [`ListenFullFetchError::Interrupted`] error is returned with the listens already fetched,
+    /// and a checkpoint that can be given back to resume the fetch.
+    #[builder]
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(client, checkpoint), fields(indicatif.pb_show = tracing::field::Empty)))]
+    pub fn get_user_username_listens_full_blocking<'s>(
+        client: &'s ListenBrainzClient,
+        username: &'s str,
+        #[builder(into)] start: Option<Timestamp>,
+        #[builder(into)] end: Option<Timestamp>,
+        /// Sort the listens from newest to oldest, and remove duplicates. See [`super::ordering::sort_and_dedup_listens`]. Default to `false`
+        sorted: Option<bool>,
+        /// Resume an interrupted fetch. `username`, `start` and `end` must be the same as the interrupted fetch
+        checkpoint: Option<ListenFetchCheckpoint>,
+    ) -> Result<UserListensFullResult, ListenFullFetchError> {
+        let mut planner = ListenWindowPlanner::new_or_resume(username, start, end, checkpoint)?;
+        let mut listens = Vec::new();
+
+        while let Some(window) = planner.next_window() {
+            match send_request(client, username, window) {
+                Ok(res) => listens.extend(planner.handle_response(window, res)),
+                Err(err) => return Err(interrupt(err, &planner, listens)),
+            }
+        }
+
+        Ok(finish_fetch(&planner, listens, sorted))
+    }
+
+    /// Get all the listens in a time period as an iterator, removing the paging. This is the blocking version of [`Self::get_user_username_listens_stream`]
+    ///
+    /// The listens are yielded in chunks, one per fetched time window. The next window is only fetched once the iterator is advanced again,
+    /// so the listens can be written somewhere else without ever holding all of them in memory.
+    ///
+    /// The iterator ends after the first error. It is a [`ListenFullFetchError::Interrupted`] error
+    /// containing the checkpoint to resume the iterator from, or a [`ListenFullFetchError::CheckpointMismatch`] error
+    /// if the given checkpoint doesn't match the iterator.
+    ///
+    /// The windows are fetched from the newest to the oldest listens.
+    #[builder]
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(client, checkpoint), fields(indicatif.pb_show = tracing::field::Empty)))]
+    pub fn get_user_username_listens_stream_blocking<'s>(
+        client: &'s ListenBrainzClient,
+        username: &'s str,
+        #[builder(into)] start: Option<Timestamp>,
+        #[builder(into)] end: Option<Timestamp>,
+        /// Resume an interrupted iterator. `username`, `start` and `end` must be the same as the interrupted iterator
+        checkpoint: Option<ListenFetchCheckpoint>,
+    ) -> impl Iterator<Item = Result<Vec<UserListensListen>, ListenFullFetchError>> + 's {
+        let mut state = Some(ListenWindowPlanner::new_or_resume(
+            username, start, end, checkpoint,
+        ));
+
+        // The windows are fetched after returning, so the span must be entered again on each call to report the progress
+        #[cfg(feature = "tracing")]
+        let span = tracing::Span::current();
+
+        core::iter::from_fn(move || {
+            #[cfg(feature = "tracing")]
+            let _entered = span.enter();
+
+            let mut planner = match state.take()? {
+                Ok(planner) => planner,
+                Err(err) => return Some(Err(err)),
+            };
+
+            let window = planner.next_window()?;
+            match send_request(client, username, window) {
+                Ok(res) => {
+                    let listens = planner.handle_response(window, res);
+                    state = Some(Ok(planner));
+                    Some(Ok(listens))
+                }
+                // Return the error and end the iterator. The listens were already yielded, so none are saved
+                Err(err) => Some(Err(interrupt(err, &planner, Vec::new()))),
+            }
+        })
+    }
+}
+
+fn send_request(
+    client: &ListenBrainzClient,
+    username: &str,
+    window: (u64, u64),
+) -> Result<UserListensResponse, ListenFullFetchError> {
+    let mut req = window_request(client, username, window)?;
+
+    client
+        .send(&mut req)
+        .context(ApiRequestSnafu)?
+        .parse()
+        .context(ParserSnafu)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::api::ListenBrainzAPIEnpoints;
+    use crate::mock_server::MockListenBrainzServer;
+
+    #[test]
+    fn get_user_username_listens_full_blocking_test() {
+        let server = MockListenBrainzServer::start_with_fixtures().unwrap();
+        let client = server.client();
+
+        let req = ListenBrainzAPIEnpoints::get_user_username_listens_full_blocking()
+            .client(&client)
+            .username("RustyNova")
+            .start(1_705_000_000_i64)
+            .end(1_710_000_000_i64)
+            .call()
+            .unwrap();
+
+        assert_eq!(req.listens.len(), 4840);
+    }
+
+    #[test]
+    fn get_user_username_listens_stream_blocking_test() {
+        let server = MockListenBrainzServer::start_with_fixtures().unwrap();
+        let client = server.client();
+
+        let count = ListenBrainzAPIEnpoints::get_user_username_listens_stream_blocking()
+            .client(&client)
+            .username("RustyNova")
+            .start(1_705_000_000_i64)
+            .end(1_710_000_000_i64)
+            .call()
+            .map(|listens| listens.unwrap().len())
+            .sum::<usize>();
+
+        assert_eq!(count, 4840);
+    }
+}
@@ -0,0 +1,57 @@
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::models::timestamp::Timestamp;
+
+/// The progress of a full listen fetch. It can be persisted to resume an interrupted fetch later.
+///
+/// See [`super::ListenFullFetchError::Interrupted`]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ListenFetchCheckpoint {
+    /// The user whose listens are fetched
+    pub username: String,
+
+    /// The start of the requested time period
+    pub start: Option<Timestamp>,
+
+    /// The end of the requested time period
+    pub end: Option<Timestamp>,
+
+    /// The end of the period, as resolved when the fetch started.
+    ///
+    /// If `end` is `None`, the fetch is open ended: the listens made after this timestamp are fetched too once it's resumed
+    pub resolved_end: u64,
+
+    /// The time ranges that weren't fetched yet, from the newest to the oldest.
+    /// Every listen outside of those ranges was already retrieved
+    pub remaining_ranges: Vec<(u64, u64)>,
+
+    /// The timestamp of the oldest listen of the user, if known
+    pub oldest_listen_ts: Option<u64>,
+
+    /// The timestamp of the latest listen of the user, if known
+    pub latest_listen_ts: Option<u64>,
+
+    /// The estimated number of listens per seconds, if known
+    pub density: Option<f64>,
+
+    /// The number of requests sent before the checkpoint
+    pub request_count: u64,
+}
+
+impl ListenFetchCheckpoint {
+    /// Return true if there's nothing left to fetch
+    pub fn is_finished(&self) -> bool {
+        self.remaining_ranges.is_empty()
+    }
+
+    /// Return true if the checkpoint was saved by a fetch of the same user and time period
+    pub fn matches(
+        &self,
+        username: &str,
+        start: Option<Timestamp>,
+        end: Option<Timestamp>,
+    ) -> bool {
+        self.username == username && self.start == start && self.end == end
+    }
+}
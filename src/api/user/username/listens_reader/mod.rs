@@ -0,0 +1,382 @@
+use api_bindium::ApiRequest;
+use api_bindium::ApiRequestError;
+use api_bindium::endpoints::UriBuilderError;
+use snafu::IntoError as _;
+use snafu::ResultExt as _;
+use snafu::Snafu;
+
+#[cfg(feature = "async")]
+use crate::api::ListenBrainzAPIEnpoints;
+use crate::api::user::username::listens::UserListensListen;
+use crate::api::user::username::listens::UserListensResponse;
+use crate::api::user::username::listens_reader::checkpoint::ListenFetchCheckpoint;
+use crate::api::user::username::listens_reader::ordering::sort_and_dedup_listens;
+use crate::api::user::username::listens_reader::planner::ListenWindowPlanner;
+use crate::client::ListenBrainzClient;
+#[cfg(feature = "async")]
+use crate::models::timestamp::Timestamp;
+use crate::parser::ListenBrainzParser;
+use crate::parser::ResponseError;
+
+#[cfg(feature = "sync")]
+pub mod blocking;
+pub mod checkpoint;
+pub mod ordering;
+mod planner;
+#[cfg(feature = "async")]
+pub mod stream;
+
+#[cfg(feature = "async")]
+#[bon::bon]
+impl ListenBrainzAPIEnpoints {
+    /// Get all the listens in a time period, removing the paging.
+    ///
+    /// The size of the fetched time windows adapts to the listen density of the user, so light listeners only need a few requests,
+    /// while heavy listeners don't get overflowing windows.
+    ///
+    /// Due to implementation details and quirks in the API, the listens may not be sorted and may contain duplicates.
+    /// Set `sorted` to get the listens from newest to oldest, without duplicates.
+    ///
+    /// If a request fails, a [`ListenFullFetchError::Interrupted`] error is returned with the listens already fetched,
+    /// and a checkpoint that can be given back to resume the fetch.
+    #[builder]
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(client, checkpoint), fields(indicatif.pb_show = tracing::field::Empty)))]
+    pub async fn get_user_username_listens_full<'s>(
+        client: &'s ListenBrainzClient,
+        username: &'s str,
+        #[builder(into)] start: Option<Timestamp>,
+        #[builder(into)] end: Option<Timestamp>,
+        /// How many time windows can be fetched at the same time. Default to 1 (sequential).
+        ///
+        /// The requests are still going through the rate limiter of the client if the `rate_limit` feature is enabled
+        parallelism: Option<usize>,
+        /// Sort the listens from newest to oldest, and remove duplicates. See [`sort_and_dedup_listens`]. Default to `false`
+        sorted: Option<bool>,
+        /// Resume an interrupted fetch. `username`, `start` and `end` must be the same as the interrupted fetch
+        checkpoint: Option<ListenFetchCheckpoint>,
+    ) -> Result<UserListensFullResult, ListenFullFetchError> {
+        let parallelism = parallelism.unwrap_or(1).max(1);
+        let mut planner = ListenWindowPlanner::new_or_resume(username, start, end, checkpoint)?;
+        let mut listens = Vec::new();
+
+        loop {
+            let windows = core::iter::from_fn(|| planner.next_window())
+                .take(parallelism)
+                .collect::<Vec<_>>();
+
+            if windows.is_empty() {
+                break;
+            }
+
+            let responses = send_requests(client, username, &windows).await;
+
+            // Save the successful windows before erroring
+            let mut error = None;
+            for (window, res) in windows.into_iter().zip(responses) {
+                match res {
+                    Ok(res) => listens.extend(planner.handle_response(window, res)),
+                    Err(err) => error = error.or(Some(err)),
+                }
+            }
+
+            if let Some(err) = error {
+                return Err(interrupt(err, &planner, listens));
+            }
+        }
+
+        Ok(finish_fetch(&planner, listens, sorted))
+    }
+}
+
+/// The result of [`crate::ListenBrainzAPIEnpoints::get_user_username_listens_full`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UserListensFullResult {
+    /// The fetched listens
+    pub listens: Vec<UserListensListen>,
+
+    /// The number of requests sent to fetch the listens
+    pub request_count: u64,
+}
+
+/// Build the result of a full fetch
+fn finish_fetch(
+    planner: &ListenWindowPlanner,
+    mut listens: Vec<UserListensListen>,
+    sorted: Option<bool>,
+) -> UserListensFullResult {
+    if sorted.unwrap_or(false) {
+        sort_and_dedup_listens(&mut listens);
+    }
+
+    UserListensFullResult {
+        listens,
+        request_count: planner.request_count(),
+    }
+}
+
+/// Create the request fetching the listens of a window
+fn window_request(
+    client: &ListenBrainzClient,
+    username: &str,
+    (start, end): (u64, u64),
+) -> Result<ApiRequest<ListenBrainzParser<UserListensResponse>>, ListenFullFetchError> {
+    client
+        .endpoints()
+        .get_user_username_listens()
+        .username(username)
+        .min_ts(start)
+        .max_ts(end)
+        .count(planner::MAX_LISTENS_PER_REQUEST)
+        .call()
+        .context(UriBuilderSnafu)
+}
+
+/// Wrap an error into a [`ListenFullFetchError::Interrupted`] error, saving the progress of the fetch
+fn interrupt(
+    err: ListenFullFetchError,
+    planner: &ListenWindowPlanner,
+    listens: Vec<UserListensListen>,
+) -> ListenFullFetchError {
+    InterruptedSnafu {
+        listens,
+        checkpoint: planner.checkpoint(),
+    }
+    .into_error(err)
+}
+
+/// Fetch multiple windows concurrently, and return the responses in the same order
+#[cfg(feature = "async")]
+async fn send_requests(
+    client: &ListenBrainzClient,
+    username: &str,
+    windows: &[(u64, u64)],
+) -> Vec<Result<UserListensResponse, ListenFullFetchError>> {
+    // No need to spawn tasks for a single request
+    if let [(start, end)] = windows {
+        return vec![send_request(client, username, *start, *end).await];
+    }
+
+    let tasks = windows
+        .iter()
+        .map(|&(start, end)| {
+            let client = client.clone();
+            let username = username.to_string();
+
+            async move { send_request(&client, &username, start, end).await }
+        })
+        .collect::<Vec<_>>();
+
+    client.run_concurrently(tasks).await
+}
+
+#[cfg(feature = "async")]
+//#[cfg_attr(feature = "hotpath", hotpath::measure)]
+async fn send_request(
+    client: &ListenBrainzClient,
+    username: &str,
+    start: u64,
+    end: u64,
+) -> Result<UserListensResponse, ListenFullFetchError> {
+    let mut req = window_request(client, username, (start, end))?;
+
+    client
+        .send_async(&mut req)
+        .await
+        .context(ApiRequestSnafu)?
+        .parse()
+        .context(ParserSnafu)
+}
+
+#[derive(Debug, Snafu)]
+pub enum ListenFullFetchError {
+    ApiRequestError {
+        source: ApiRequestError,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+
+    UriBuilderError {
+        source: UriBuilderError,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+
+    ParserError {
+        source: ResponseError,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+
+    /// The fetch got interrupted by an error. The listens fetched before the error are kept,
+    /// and the fetch can be resumed by giving back the checkpoint
+    #[snafu(display("The listen fetch got interrupted after retrieving {} listens", listens.len()))]
+    Interrupted {
+        #[snafu(source(from(ListenFullFetchError, Box::new)))]
+        source: Box<ListenFullFetchError>,
+
+        /// The listens fetched before the error
+        listens: Vec<UserListensListen>,
+
+        /// The progress of the fetch
+        checkpoint: ListenFetchCheckpoint,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+
+    /// The checkpoint was saved by the fetch of another user or time period
+    #[snafu(display("The checkpoint doesn't match the listen fetch of `{username}`"))]
+    CheckpointMismatch {
+        username: String,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+}
+
+impl ListenFullFetchError {
+    /// Return the listens fetched before the error, and the checkpoint to resume the fetch
+    pub fn into_partial(self) -> Option<(Vec<UserListensListen>, ListenFetchCheckpoint)> {
+        match self {
+            Self::Interrupted {
+                listens,
+                checkpoint,
+                ..
+            } => Some((listens, checkpoint)),
+            Self::ApiRequestError { .. }
+            | Self::UriBuilderError { .. }
+            | Self::ParserError { .. }
+            | Self::CheckpointMismatch { .. } => None,
+        }
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "async")]
+mod test {
+
+    use std::collections::HashSet;
+
+    use macro_rules_attribute::apply;
+
+    use crate::api::ListenBrainzAPIEnpoints;
+    use crate::api::user::username::listens::UserListensListen;
+    use crate::mock_server::MockListenBrainzServer;
+    use crate::mock_server::data::FIXTURE_USER;
+    use crate::mock_server::data::MockData;
+
+    #[apply(smol_macros::test!)]
+    async fn get_user_username_listens_test() {
+        let server = MockListenBrainzServer::start_with_fixtures().unwrap();
+        let client = server.client();
+
+        let req = ListenBrainzAPIEnpoints::get_user_username_listens_full()
+            .client(&client)
+            .username("RustyNova")
+            .start(1_705_000_000_i64)
+            .end(1_710_000_000_i64)
+            .call()
+            .await
+            .unwrap();
+
+        assert_eq!(req.listens.len(), 4840);
+    }
+
+    #[apply(smol_macros::test!)]
+    async fn get_user_username_listens_parallel_test() {
+        let server = MockListenBrainzServer::start_with_fixtures().unwrap();
+        let client = server.client();
+
+        let req = ListenBrainzAPIEnpoints::get_user_username_listens_full()
+            .client(&client)
+            .username("RustyNova")
+            .start(1_705_000_000_i64)
+            .end(1_710_000_000_i64)
+            .parallelism(4)
+            .call()
+            .await
+            .unwrap();
+
+        assert_eq!(req.listens.len(), 4840);
+    }
+
+    /// The timestamp of the first listen of [`overlapping_windows_server`]
+    const OVERLAPPING_LISTENS_START: i64 = 1_763_000_000;
+
+    /// A mock server where the fixture user listened to `listen_count` tracks, two per second.
+    /// The windows of a full fetch overlap on their bounds, so they return duplicates
+    fn overlapping_windows_server(listen_count: i64) -> MockListenBrainzServer {
+        let mut data = MockData::fixtures();
+        data.inclusive_listen_bounds = true;
+
+        let template = data.users[FIXTURE_USER].listens[0].clone();
+        let listens = (0..listen_count)
+            .map(|i| {
+                let mut listen = template.clone();
+                listen.listened_at = OVERLAPPING_LISTENS_START + i / 2;
+                listen.inserted_at = listen.listened_at;
+                listen.recording_msid = data.new_msid();
+                listen
+            })
+            .collect();
+        data.users.get_mut(FIXTURE_USER).unwrap().listens = listens;
+
+        MockListenBrainzServer::start_with_data(data).unwrap()
+    }
+
+    /// Check that the listens are sorted from newest to oldest, and are the same as `unsorted` without the duplicates
+    fn assert_sorted_and_deduped(sorted: &[UserListensListen], unsorted: &[UserListensListen]) {
+        for pair in sorted.windows(2) {
+            let key = |i: usize| (-pair[i].listened_at, pair[i].recording_msid.as_str());
+            assert!(key(0) < key(1));
+        }
+
+        let key = |listen: &UserListensListen| (listen.listened_at, listen.recording_msid.clone());
+        let distinct = unsorted.iter().map(key).collect::<HashSet<_>>();
+        assert_eq!(sorted.len(), distinct.len());
+        assert_eq!(sorted.iter().map(key).collect::<HashSet<_>>(), distinct);
+    }
+
+    #[apply(smol_macros::test!)]
+    async fn get_user_username_listens_sorted_test() {
+        let server = overlapping_windows_server(2500);
+        let client = server.client();
+
+        let fetch = |sorted| {
+            ListenBrainzAPIEnpoints::get_user_username_listens_full()
+                .client(&client)
+                .username(FIXTURE_USER)
+                .start(OVERLAPPING_LISTENS_START - 1)
+                .end(OVERLAPPING_LISTENS_START + 1500)
+                .parallelism(3)
+                .sorted(sorted)
+                .call()
+        };
+
+        let unsorted = fetch(false).await.unwrap().listens;
+        // The overlapping windows returned duplicates
+        assert!(unsorted.len() > 2500);
+
+        let sorted = fetch(true).await.unwrap().listens;
+        assert_eq!(sorted.len(), 2500);
+        assert_sorted_and_deduped(&sorted, &unsorted);
+    }
+}
@@ -0,0 +1,62 @@
+use crate::api::user::username::listens::UserListensListen;
+
+/// Sort the listens from newest to oldest, and remove the duplicates.
+///
+/// Two listens are considered duplicates if they share the same `listened_at` and `recording_msid`
+pub fn sort_and_dedup_listens(listens: &mut Vec<UserListensListen>) {
+    listens.sort_unstable_by(|a, b| {
+        b.listened_at
+            .cmp(&a.listened_at)
+            .then_with(|| a.recording_msid.cmp(&b.recording_msid))
+    });
+
+    listens.dedup_by(|a, b| a.listened_at == b.listened_at && a.recording_msid == b.recording_msid);
+}
+
+#[cfg(test)]
+mod test {
+    use crate::api::user::username::listens::UserListensResponse;
+    use crate::api::user::username::listens_reader::ordering::sort_and_dedup_listens;
+
+    /// Load the listen windows recorded in the cassette `fixtures/cassettes/{name}.json`, in their recording order
+    fn recorded_windows(name: &str) -> Vec<UserListensResponse> {
+        let path = format!(
+            "{}/fixtures/cassettes/{name}.json",
+            env!("CARGO_MANIFEST_DIR")
+        );
+        let cassette: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+
+        cassette["interactions"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|interaction| {
+                serde_json::from_str(interaction["response"]["body"].as_str().unwrap()).unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn sort_and_dedup_listens_test() {
+        // Two windows sharing their boundary second, recorded from the mock server of `overlapping_windows_server`
+        let [newer, older] =
+            <[_; 2]>::try_from(recorded_windows("overlapping_listen_windows")).unwrap();
+
+        // Fetched out of order
+        let mut listens = older.payload.listens;
+        listens.extend(newer.payload.listens);
+        assert_eq!(listens.len(), 24);
+
+        sort_and_dedup_listens(&mut listens);
+
+        assert_eq!(listens.len(), 22);
+        for pair in listens.windows(2) {
+            let key = |i: usize| (-pair[i].listened_at, pair[i].recording_msid.as_str());
+            assert!(key(0) < key(1));
+        }
+
+        // Listens sharing a timestamp aren't duplicates if their MSIDs are different
+        assert_eq!(listens[0].listened_at, listens[1].listened_at);
+    }
+}
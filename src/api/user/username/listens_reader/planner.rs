@@ -0,0 +1,419 @@
+use std::collections::VecDeque;
+
+use snafu::ensure;
+
+use crate::api::user::username::listens::UserListensListen;
+use crate::api::user::username::listens::UserListensResponse;
+use crate::api::user::username::listens_reader::CheckpointMismatchSnafu;
+use crate::api::user::username::listens_reader::ListenFullFetchError;
+use crate::api::user::username::listens_reader::checkpoint::ListenFetchCheckpoint;
+use crate::inner_macros::pg_counted;
+use crate::inner_macros::pg_inc;
+use crate::models::timestamp::Timestamp;
+
+/// The maximum number of listens returned by a single request
+pub(super) const MAX_LISTENS_PER_REQUEST: u64 = 1000;
+
+/// The number of listens we aim to get per window. It's lower than [`MAX_LISTENS_PER_REQUEST`]
+/// to leave a margin for bursts of listens
+const TARGET_LISTENS_PER_WINDOW: f64 = 750.0;
+
+/// The smallest time window generated, in seconds
+const MIN_WINDOW_SIZE: u64 = 60;
+
+/// Plan the time windows to fetch to retrieve all the listens of a time period.
+///
+/// The period is walked from the newest to the oldest listens. The first window spans the whole period, and the following ones
+/// are sized using the listen density observed in the previous responses.
+///
+/// As the api returns the newest listens of a window first, the listens of an overflowing window are kept,
+/// and only the part of the window older than the returned listens is planned again.
+#[derive(Debug)]
+pub(super) struct ListenWindowPlanner {
+    /// The user whose listens are fetched
+    username: String,
+
+    /// The requested time period, as given by the caller
+    start: Option<Timestamp>,
+    end: Option<Timestamp>,
+
+    /// The end of the period, as resolved when the planning started
+    resolved_end: u64,
+
+    /// The time ranges that still need to be fetched. They are cut into windows when needed
+    ranges: VecDeque<(u64, u64)>,
+
+    /// The windows that got planned, but didn't receive their response yet
+    in_flight: Vec<(u64, u64)>,
+
+    /// The timestamp of the oldest listen of the user. No need to fetch windows before it
+    min_start: Option<u64>,
+
+    /// The timestamp of the latest listen of the user.
+    max_end: Option<u64>,
+
+    /// The estimated number of listens per seconds
+    density: Option<f64>,
+
+    /// The number of requests sent
+    request_count: u64,
+}
+
+impl ListenWindowPlanner {
+    pub fn new(username: &str, start: Option<Timestamp>, end: Option<Timestamp>) -> Self {
+        let range_start = start
+            .map(|start| start.as_unsigned_secs())
+            .unwrap_or_default();
+        let range_end = end.unwrap_or_else(Timestamp::now).as_unsigned_secs();
+
+        pg_counted!(range_end.saturating_sub(range_start), "Fetching listens");
+
+        Self {
+            username: username.to_string(),
+            start,
+            end,
+            resolved_end: range_end,
+            ranges: VecDeque::from([(range_start, range_end)]),
+            in_flight: Vec::new(),
+            min_start: None,
+            max_end: None,
+            density: None,
+            request_count: 0,
+        }
+    }
+
+    /// Resume from the checkpoint if there's one, or start planning the period.
+    ///
+    /// The checkpoint must have been saved by a fetch of the same user and time period
+    pub fn new_or_resume(
+        username: &str,
+        start: Option<Timestamp>,
+        end: Option<Timestamp>,
+        checkpoint: Option<ListenFetchCheckpoint>,
+    ) -> Result<Self, ListenFullFetchError> {
+        let Some(checkpoint) = checkpoint else {
+            return Ok(Self::new(username, start, end));
+        };
+
+        ensure!(
+            checkpoint.matches(username, start, end),
+            CheckpointMismatchSnafu { username }
+        );
+
+        Ok(Self::from_checkpoint(checkpoint))
+    }
+
+    /// Resume the planning from a checkpoint.
+    ///
+    /// If the fetch is open ended, the listens made since the checkpoint got saved are planned too
+    pub fn from_checkpoint(checkpoint: ListenFetchCheckpoint) -> Self {
+        let mut ranges = VecDeque::from(checkpoint.remaining_ranges);
+        let mut resolved_end = checkpoint.resolved_end;
+
+        if checkpoint.end.is_none() {
+            let now = Timestamp::now().as_unsigned_secs();
+
+            if now > resolved_end {
+                // `max_ts` was exclusive, so the listens at `resolved_end` weren't fetched yet
+                ranges.push_front((resolved_end.saturating_sub(1), now));
+                resolved_end = now;
+            }
+        }
+
+        let remaining = ranges
+            .iter()
+            .map(|(start, end)| end.saturating_sub(*start))
+            .sum::<u64>();
+        pg_counted!(remaining, "Fetching listens");
+
+        Self {
+            username: checkpoint.username,
+            start: checkpoint.start,
+            end: checkpoint.end,
+            resolved_end,
+            ranges,
+            in_flight: Vec::new(),
+            min_start: checkpoint.oldest_listen_ts,
+            max_end: checkpoint.latest_listen_ts,
+            density: checkpoint.density,
+            request_count: checkpoint.request_count,
+        }
+    }
+
+    /// Save the state of the planning. The windows that didn't receive their response are planned again
+    pub fn checkpoint(&self) -> ListenFetchCheckpoint {
+        ListenFetchCheckpoint {
+            username: self.username.clone(),
+            start: self.start,
+            end: self.end,
+            resolved_end: self.resolved_end,
+            remaining_ranges: self
+                .in_flight
+                .iter()
+                .chain(self.ranges.iter())
+                .copied()
+                .collect(),
+            oldest_listen_ts: self.min_start,
+            latest_listen_ts: self.max_end,
+            density: self.density,
+            request_count: self.request_count,
+        }
+    }
+
+    /// The number of requests sent so far
+    pub fn request_count(&self) -> u64 {
+        self.request_count
+    }
+
+    /// Return the next window to fetch, or `None` if every window got planned
+    pub fn next_window(&mut self) -> Option<(u64, u64)> {
+        while let Some((start, end)) = self.ranges.pop_front() {
+            // Prevent fetching a period that is before any listen
+            if self.min_start.is_some_and(|min_start| end <= min_start) {
+                pg_inc!(end - start);
+                continue;
+            }
+
+            let lowest_start = self
+                .min_start
+                .map_or(start, |min_start| min_start.saturating_sub(1))
+                .max(start);
+
+            let window_start = match self.density {
+                // We know nothing yet. Let's try the whole range in one go
+                None => lowest_start,
+                Some(density) => {
+                    let size = if density > 0.0 {
+                        (TARGET_LISTENS_PER_WINDOW / density) as u64
+                    } else {
+                        u64::MAX
+                    };
+
+                    end.saturating_sub(size.max(MIN_WINDOW_SIZE))
+                        .max(lowest_start)
+                }
+            };
+
+            pg_inc!(lowest_start - start);
+
+            // `min_ts` and `max_ts` are exclusive, so the rest of the range must include `window_start`
+            if window_start > lowest_start {
+                self.ranges.push_front((lowest_start, window_start + 1));
+            }
+
+            self.in_flight.push((window_start, end));
+            return Some((window_start, end));
+        }
+
+        None
+    }
+
+    /// Process the response of a window, and return the listens of the window that are guaranteed to be complete.
+    ///
+    /// If the window overflowed, the part of the window that is older than the returned listens is scheduled for refetching
+    pub fn handle_response(
+        &mut self,
+        window: (u64, u64),
+        res: UserListensResponse,
+    ) -> Vec<UserListensListen> {
+        let (start, end) = window;
+        self.in_flight.retain(|in_flight| *in_flight != window);
+        self.request_count += 1;
+        self.min_start = u64::try_from(res.payload.oldest_listen_ts).ok();
+        self.max_end = u64::try_from(res.payload.latest_listen_ts).ok();
+
+        let mut listens = res.payload.listens;
+
+        // Check if the period overflowed
+        if listens.len() as u64 == MAX_LISTENS_PER_REQUEST {
+            let oldest = listens
+                .iter()
+                .filter_map(|listen| u64::try_from(listen.listened_at).ok())
+                .min()
+                .unwrap_or(start)
+                .max(start);
+
+            if oldest + 1 < end {
+                // The listens newer than the oldest returned one are complete. Refetch the rest
+                listens
+                    .retain(|listen| u64::try_from(listen.listened_at).is_ok_and(|ts| ts > oldest));
+                self.ranges.push_front((start, oldest + 1));
+            } else if oldest > start {
+                // More than a full page of listens in a single second. We can't get the others, so skip the second
+                self.ranges.push_front((start, oldest));
+            }
+
+            self.observe(listens.len(), (oldest, end));
+            pg_inc!(end - oldest);
+        } else {
+            self.observe(listens.len(), window);
+            pg_inc!(end - start);
+        }
+
+        listens
+    }
+
+    /// Update the listen density with the listens of a window
+    fn observe(&mut self, listen_count: usize, (start, end): (u64, u64)) {
+        // Don't count the time before the first listen and after the last listen of the user
+        let start = self
+            .min_start
+            .map_or(start, |min_start| start.max(min_start.saturating_sub(1)));
+        let end = self.max_end.map_or(end, |max_end| end.min(max_end + 1));
+        let duration = end.saturating_sub(start).max(1) as f64;
+
+        let observed = listen_count as f64 / duration;
+        self.density = Some(match self.density {
+            Some(density) => (density + observed) / 2.0,
+            None => observed,
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::api::user::username::listens::UserListensResponse;
+    use crate::api::user::username::listens_reader::planner::ListenWindowPlanner;
+    use crate::models::timestamp::Timestamp;
+
+    fn planner() -> ListenWindowPlanner {
+        ListenWindowPlanner::new(
+            "RustyNova",
+            Some(Timestamp::from_secs(0)),
+            Some(Timestamp::from_secs(2_000_000)),
+        )
+    }
+
+    fn response(listened_ats: &[i64]) -> UserListensResponse {
+        let listens = listened_ats
+            .iter()
+            .map(|listened_at| {
+                serde_json::json!({
+                    "user_name": "RustyNova",
+                    "inserted_at": listened_at,
+                    "listened_at": listened_at,
+                    "recording_msid": "cfb002e7-f093-4678-8bf7-fb139a4f718c",
+                    "track_metadata": {
+                        "artist_name": "Kikuo",
+                        "track_name": "Aishite Aishite Aishite",
+                        "release_name": null,
+                        "additional_info": {},
+                        "mbid_mapping": null
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+
+        serde_json::from_value(serde_json::json!({
+            "payload": {
+                "count": listens.len(),
+                "latest_listen_ts": 1_000_000,
+                "oldest_listen_ts": 100_000,
+                "user_id": "RustyNova",
+                "listens": listens,
+            }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn light_listener_test() {
+        let mut planner = planner();
+
+        // Few listens: A single request is enough
+        let window = planner.next_window().unwrap();
+        assert_eq!(window, (0, 2_000_000));
+        assert_eq!(
+            planner
+                .handle_response(window, response(&[500_000, 100_000]))
+                .len(),
+            2
+        );
+
+        assert_eq!(planner.next_window(), None);
+        assert_eq!(planner.request_count(), 1);
+    }
+
+    #[test]
+    fn overflowing_window_test() {
+        let mut planner = planner();
+
+        // One listen per second from 999_001 to 1_000_000
+        let window = planner.next_window().unwrap();
+        let listened_ats = (999_001..=1_000_000).rev().collect::<Vec<_>>();
+        let listens = planner.handle_response(window, response(&listened_ats));
+
+        // The oldest listen is fetched again with the rest of the window
+        assert_eq!(listens.len(), 999);
+
+        // The density is ~ 1 listen per second, so the rest is cut in smaller windows
+        let first = planner.next_window().unwrap();
+        let second = planner.next_window().unwrap();
+        assert_eq!(first.1, 999_002);
+        assert!(first.1 - first.0 < 5000);
+        assert_eq!(second.1, first.0 + 1);
+        assert_eq!(planner.request_count(), 1);
+    }
+
+    #[test]
+    fn checkpoint_test() {
+        let mut planner = planner();
+
+        let window = planner.next_window().unwrap();
+        let listened_ats = (999_001..=1_000_000).rev().collect::<Vec<_>>();
+        planner.handle_response(window, response(&listened_ats));
+
+        // A window got planned but never received its response
+        let lost = planner.next_window().unwrap();
+
+        let checkpoint = planner.checkpoint();
+        assert_eq!(checkpoint.remaining_ranges[0], lost);
+        assert_eq!(checkpoint.request_count, 1);
+
+        // The checkpoint only resumes the same fetch
+        assert!(
+            ListenWindowPlanner::new_or_resume(
+                "Other",
+                Some(Timestamp::from_secs(0)),
+                Some(Timestamp::from_secs(2_000_000)),
+                Some(checkpoint.clone()),
+            )
+            .is_err()
+        );
+        assert!(
+            ListenWindowPlanner::new_or_resume("RustyNova", None, None, Some(checkpoint.clone()))
+                .is_err()
+        );
+
+        // The resumed planner continues with the lost window
+        let mut resumed = ListenWindowPlanner::new_or_resume(
+            "RustyNova",
+            Some(Timestamp::from_secs(0)),
+            Some(Timestamp::from_secs(2_000_000)),
+            Some(checkpoint),
+        )
+        .unwrap();
+        assert_eq!(resumed.next_window(), Some(lost));
+    }
+
+    #[test]
+    fn open_ended_checkpoint_test() {
+        let mut planner = ListenWindowPlanner::new("RustyNova", None, None);
+        let window = planner.next_window().unwrap();
+        planner.handle_response(window, response(&[500_000, 100_000]));
+
+        // The fetch finished a while ago
+        let mut checkpoint = planner.checkpoint();
+        assert!(checkpoint.is_finished());
+        checkpoint.resolved_end -= 100;
+
+        // The listens made since then are fetched on resume
+        let mut resumed =
+            ListenWindowPlanner::new_or_resume("RustyNova", None, None, Some(checkpoint.clone()))
+                .unwrap();
+        let (start, end) = resumed.next_window().unwrap();
+        assert_eq!(start, checkpoint.resolved_end - 1);
+        assert!(end >= checkpoint.resolved_end + 100);
+        assert!(resumed.checkpoint().resolved_end >= checkpoint.resolved_end + 100);
+    }
+}
@@ -0,0 +1,98 @@
+use futures_lite::Stream;
+use futures_lite::stream;
+
+use crate::api::ListenBrainzAPIEnpoints;
+use crate::api::user::username::listens::UserListensListen;
+use crate::api::user::username::listens_reader::ListenFullFetchError;
+use crate::api::user::username::listens_reader::checkpoint::ListenFetchCheckpoint;
+use crate::api::user::username::listens_reader::interrupt;
+use crate::api::user::username::listens_reader::planner::ListenWindowPlanner;
+use crate::api::user::username::listens_reader::send_request;
+use crate::client::ListenBrainzClient;
+use crate::models::timestamp::Timestamp;
+
+#[bon::bon]
+impl ListenBrainzAPIEnpoints {
+    /// Get all the listens in a time period as a stream, removing the paging.
+    ///
+    /// The listens are yielded in chunks, one per fetched time window. The next window is only fetched once the stream is polled again,
+    /// so the listens can be written somewhere else without ever holding all of them in memory.
+    ///
+    /// The stream ends after the first error. It is a [`ListenFullFetchError::Interrupted`] error
+    /// containing the checkpoint to resume the stream from, or a [`ListenFullFetchError::CheckpointMismatch`] error
+    /// if the given checkpoint doesn't match the stream.
+    ///
+    /// The windows are fetched from the newest to the oldest listens.
+    #[builder]
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(client, checkpoint), fields(indicatif.pb_show = tracing::field::Empty)))]
+    pub fn get_user_username_listens_stream<'s>(
+        client: &'s ListenBrainzClient,
+        username: &'s str,
+        #[builder(into)] start: Option<Timestamp>,
+        #[builder(into)] end: Option<Timestamp>,
+        /// Resume an interrupted stream. `username`, `start` and `end` must be the same as the interrupted stream
+        checkpoint: Option<ListenFetchCheckpoint>,
+    ) -> impl Stream<Item = Result<Vec<UserListensListen>, ListenFullFetchError>> + 's {
+        let state = Some(ListenWindowPlanner::new_or_resume(
+            username, start, end, checkpoint,
+        ));
+
+        // The windows are fetched after returning, so the span must be entered again on each poll to report the progress
+        #[cfg(feature = "tracing")]
+        let span = tracing::Span::current();
+
+        stream::unfold(state, move |state| {
+            let step = async move {
+                let mut planner = match state? {
+                    Ok(planner) => planner,
+                    Err(err) => return Some((Err(err), None)),
+                };
+
+                let window = planner.next_window()?;
+                let res = match send_request(client, username, window.0, window.1).await {
+                    Ok(res) => res,
+                    // Return the error and end the stream. The listens were already yielded, so none are saved
+                    Err(err) => return Some((Err(interrupt(err, &planner, Vec::new())), None)),
+                };
+
+                let listens = planner.handle_response(window, res);
+                Some((Ok(listens), Some(Ok(planner))))
+            };
+
+            #[cfg(feature = "tracing")]
+            let step = tracing::Instrument::instrument(step, span.clone());
+
+            step
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use futures_lite::StreamExt as _;
+    use macro_rules_attribute::apply;
+
+    use crate::api::ListenBrainzAPIEnpoints;
+    use crate::mock_server::MockListenBrainzServer;
+
+    #[apply(smol_macros::test!)]
+    async fn get_user_username_listens_stream_test() {
+        let server = MockListenBrainzServer::start_with_fixtures().unwrap();
+        let client = server.client();
+
+        let stream = ListenBrainzAPIEnpoints::get_user_username_listens_stream()
+            .client(&client)
+            .username("RustyNova")
+            .start(1_705_000_000_i64)
+            .end(1_710_000_000_i64)
+            .call();
+        futures_lite::pin!(stream);
+
+        let mut count = 0;
+        while let Some(listens) = stream.next().await {
+            count += listens.unwrap().len();
+        }
+
+        assert_eq!(count, 4840);
+    }
+}
@@ -0,0 +1,580 @@
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::api::user::username::listens::UserListensListen;
+use crate::models::mbid::RecordingMsid;
+
+/// The number of listens scanned for backdated listens by default
+pub const DEFAULT_BACKDATED_SCAN_LIMIT: u64 = 10_000;
+
+/// The state of an incremental listen sync of a user.
+///
+/// Persist it between syncs so that only the listens inserted since the last sync are fetched.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct ListenSyncCursor {
+    /// The name of the synced user
+    pub username: String,
+
+    /// The newest `listened_at` timestamp seen. `None` if the user was never synced
+    pub latest_listened_at: Option<i64>,
+
+    /// The newest `inserted_at` timestamp seen. `None` if the user was never synced
+    pub latest_inserted_at: Option<i64>,
+
+    /// The `(listened_at, recording_msid)` of the listens inserted at `latest_inserted_at`.
+    /// Other listens can be inserted in the same second (ex: bulk imports), so those are needed to tell them apart
+    #[serde(default)]
+    pub latest_inserted_listens: Vec<(i64, RecordingMsid)>,
+
+    /// The listen count of the user at the time of the sync.
+    pub listen_count: Option<u64>,
+}
+
+impl ListenSyncCursor {
+    /// Create a cursor for a user that was never synced
+    pub fn new(username: String) -> Self {
+        Self {
+            username,
+            latest_listened_at: None,
+            latest_inserted_at: None,
+            latest_inserted_listens: Vec::new(),
+            listen_count: None,
+        }
+    }
+
+    /// Return true if the listen wasn't seen when the cursor was last advanced
+    pub fn is_new(&self, listen: &UserListensListen) -> bool {
+        self.latest_inserted_at.is_none_or(|latest_inserted_at| {
+            listen.inserted_at > latest_inserted_at
+                || (listen.inserted_at == latest_inserted_at
+                    && !self.latest_inserted_listens.contains(&listen_key(listen)))
+        })
+    }
+
+    /// Move the cursor past the given listens
+    pub fn advance<'a>(&mut self, listens: impl IntoIterator<Item = &'a UserListensListen>) {
+        for listen in listens {
+            self.latest_listened_at = Some(
+                self.latest_listened_at
+                    .map_or(listen.listened_at, |ts| ts.max(listen.listened_at)),
+            );
+
+            match self.latest_inserted_at {
+                Some(latest) if latest > listen.inserted_at => {}
+                Some(latest) if latest == listen.inserted_at => {
+                    let key = listen_key(listen);
+                    if !self.latest_inserted_listens.contains(&key) {
+                        self.latest_inserted_listens.push(key);
+                    }
+                }
+                _ => {
+                    self.latest_inserted_at = Some(listen.inserted_at);
+                    self.latest_inserted_listens = vec![listen_key(listen)];
+                }
+            }
+        }
+    }
+}
+
+/// Identify a listen. Two listens with the same key are duplicates
+fn listen_key(listen: &UserListensListen) -> (i64, RecordingMsid) {
+    (listen.listened_at, listen.recording_msid.clone())
+}
+
+/// The result of [`crate::ListenBrainzAPIEnpoints::sync_user_listens`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ListenSyncResult {
+    /// The listens inserted since the previous sync
+    pub listens: Vec<UserListensListen>,
+
+    /// The updated cursor. Persist it for the next sync
+    pub cursor: ListenSyncCursor,
+
+    /// Whether backdated listens (Listens inserted since the previous sync, but listened before it) were found
+    pub backdated_listens_found: bool,
+
+    /// Whether the scan for backdated listens stopped at its limit, before finding all the listens implied by the listen count
+    pub backdated_scan_truncated: bool,
+}
+
+#[cfg(feature = "async")]
+#[bon::bon]
+impl crate::ListenBrainzAPIEnpoints {
+    /// Fetch the listens inserted since the last sync.
+    ///
+    /// The listens listened after the cursor are fetched first. Then, the listen count of the user is compared
+    /// to the number of listens we know of. If it's higher, some backdated listens were imported since the last sync,
+    /// and the older listens are scanned, from the newest to the oldest, to find the ones inserted after the cursor.
+    /// The scan stops once the missing listens are found, or after `backdated_scan_limit` listens.
+    ///
+    /// The listen count can't tell apart deletions and imports, so a listen deleted during an import hides a backdated listen.
+    ///
+    /// Please note that the listen count of the server is cached, and may lag behind recent submissions.
+    #[builder]
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(client, cursor), fields(username = cursor.username)))]
+    pub async fn sync_user_listens(
+        client: &crate::ListenBrainzClient,
+        cursor: &ListenSyncCursor,
+        /// Whether to check for backdated listens. Default to `true`
+        detect_backdated: Option<bool>,
+        /// The maximum number of listens scanned for backdated listens. Default to [`DEFAULT_BACKDATED_SCAN_LIMIT`]
+        backdated_scan_limit: Option<u64>,
+        /// Scan the whole listen history if needed to find the backdated listens, ignoring `backdated_scan_limit`. Default to `false`
+        full_backdated_scan: Option<bool>,
+    ) -> Result<ListenSyncResult, ListenSyncError> {
+        use futures_lite::StreamExt as _;
+        use snafu::ResultExt as _;
+
+        use crate::api::user::username::listen_count::fetch_listen_count;
+
+        // Cached windows or listen count would hide the listens inserted since the previous sync
+        let client = &client.bypassing_cache();
+        let username = cursor.username.as_str();
+
+        let listens = crate::ListenBrainzAPIEnpoints::get_user_username_listens_full()
+            .client(client)
+            .username(username)
+            // `min_ts` is exclusive, so we step back a second to not miss listens sharing the cursor's timestamp
+            .maybe_start(cursor.latest_listened_at.map(|ts| ts - 1))
+            .call()
+            .await
+            .context(ListenFullFetchSnafu)?
+            .listens;
+
+        let listen_count = fetch_listen_count(client, username)
+            .await
+            .context(ListensSnapshotSnafu)?;
+
+        let mut sync = ListenSync::new(
+            cursor,
+            listens,
+            listen_count,
+            detect_backdated,
+            backdated_scan_limit,
+            full_backdated_scan,
+        );
+
+        if let Some(latest_listened_at) = sync.backdated_scan_end() {
+            let stream = crate::ListenBrainzAPIEnpoints::get_user_username_listens_stream()
+                .client(client)
+                .username(username)
+                .end(latest_listened_at + 1)
+                .call();
+            futures_lite::pin!(stream);
+
+            while let Some(chunk) = stream.next().await {
+                if sync.scan_chunk(chunk.context(ListenFullFetchSnafu)?) {
+                    break;
+                }
+            }
+        }
+
+        Ok(sync.finish())
+    }
+}
+
+#[cfg(feature = "sync")]
+#[bon::bon]
+impl crate::ListenBrainzAPIEnpoints {
+    /// Fetch the listens inserted since the last sync. This is the blocking version of [`Self::sync_user_listens`]
+    ///
+    /// The listens listened after the cursor are fetched first. Then, the listen count of the user is compared
+    /// to the number of listens we know of. If it's higher, some backdated listens were imported since the last sync,
+    /// and the older listens are scanned, from the newest to the oldest, to find the ones inserted after the cursor.
+    /// The scan stops once the missing listens are found, or after `backdated_scan_limit` listens.
+    ///
+    /// The listen count can't tell apart deletions and imports, so a listen deleted during an import hides a backdated listen.
+    ///
+    /// Please note that the listen count of the server is cached, and may lag behind recent submissions.
+    #[builder]
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(client, cursor), fields(username = cursor.username)))]
+    pub fn sync_user_listens_blocking(
+        client: &crate::ListenBrainzClient,
+        cursor: &ListenSyncCursor,
+        /// Whether to check for backdated listens. Default to `true`
+        detect_backdated: Option<bool>,
+        /// The maximum number of listens scanned for backdated listens. Default to [`DEFAULT_BACKDATED_SCAN_LIMIT`]
+        backdated_scan_limit: Option<u64>,
+        /// Scan the whole listen history if needed to find the backdated listens, ignoring `backdated_scan_limit`. Default to `false`
+        full_backdated_scan: Option<bool>,
+    ) -> Result<ListenSyncResult, ListenSyncError> {
+        use snafu::ResultExt as _;
+
+        use crate::api::user::username::listen_count::fetch_listen_count_blocking;
+
+        // Cached windows or listen count would hide the listens inserted since the previous sync
+        let client = &client.bypassing_cache();
+        let username = cursor.username.as_str();
+
+        let listens = crate::ListenBrainzAPIEnpoints::get_user_username_listens_full_blocking()
+            .client(client)
+            .username(username)
+            // `min_ts` is exclusive, so we step back a second to not miss listens sharing the cursor's timestamp
+            .maybe_start(cursor.latest_listened_at.map(|ts| ts - 1))
+            .call()
+            .context(ListenFullFetchSnafu)?
+            .listens;
+
+        let listen_count =
+            fetch_listen_count_blocking(client, username).context(ListensSnapshotSnafu)?;
+
+        let mut sync = ListenSync::new(
+            cursor,
+            listens,
+            listen_count,
+            detect_backdated,
+            backdated_scan_limit,
+            full_backdated_scan,
+        );
+
+        if let Some(latest_listened_at) = sync.backdated_scan_end() {
+            let chunks =
+                crate::ListenBrainzAPIEnpoints::get_user_username_listens_stream_blocking()
+                    .client(client)
+                    .username(username)
+                    .end(latest_listened_at + 1)
+                    .call();
+
+            for chunk in chunks {
+                if sync.scan_chunk(chunk.context(ListenFullFetchSnafu)?) {
+                    break;
+                }
+            }
+        }
+
+        Ok(sync.finish())
+    }
+}
+
+/// An ongoing sync, shared by the async and blocking versions of the sync
+#[cfg(any(feature = "sync", feature = "async"))]
+struct ListenSync<'c> {
+    cursor: &'c ListenSyncCursor,
+    listens: Vec<UserListensListen>,
+    seen: std::collections::HashSet<(i64, RecordingMsid)>,
+    listen_count: u64,
+    missing_listens: u64,
+    detect_backdated: bool,
+    scan_limit: u64,
+    scanned: u64,
+    backdated_count: u64,
+    backdated_scan_truncated: bool,
+}
+
+#[cfg(any(feature = "sync", feature = "async"))]
+impl<'c> ListenSync<'c> {
+    /// Start the sync from the listens listened after the cursor, and the current listen count of the user
+    fn new(
+        cursor: &'c ListenSyncCursor,
+        mut listens: Vec<UserListensListen>,
+        listen_count: u64,
+        detect_backdated: Option<bool>,
+        backdated_scan_limit: Option<u64>,
+        full_backdated_scan: Option<bool>,
+    ) -> Self {
+        let mut seen = std::collections::HashSet::new();
+        listens.retain(|listen| cursor.is_new(listen) && seen.insert(listen_key(listen)));
+
+        // Listens listened after the cursor are known. If the count is still too high, some backdated listens got imported
+        let missing_listens = cursor.listen_count.map_or(0, |previous_count| {
+            listen_count.saturating_sub(previous_count + listens.len() as u64)
+        });
+
+        let scan_limit = if full_backdated_scan.unwrap_or(false) {
+            u64::MAX
+        } else {
+            backdated_scan_limit.unwrap_or(DEFAULT_BACKDATED_SCAN_LIMIT)
+        };
+
+        Self {
+            cursor,
+            listens,
+            seen,
+            listen_count,
+            missing_listens,
+            detect_backdated: detect_backdated.unwrap_or(true),
+            scan_limit,
+            scanned: 0,
+            backdated_count: 0,
+            backdated_scan_truncated: false,
+        }
+    }
+
+    /// The timestamp to scan for backdated listens from, or `None` if there's no need to scan
+    fn backdated_scan_end(&self) -> Option<i64> {
+        self.cursor
+            .latest_listened_at
+            .filter(|_| self.detect_backdated && self.missing_listens > 0)
+    }
+
+    /// Look for backdated listens in a chunk of older listens. Return `true` once the scan should stop
+    fn scan_chunk(&mut self, chunk: Vec<UserListensListen>) -> bool {
+        self.scanned += chunk.len() as u64;
+
+        for listen in chunk {
+            if self.cursor.is_new(&listen) && self.seen.insert(listen_key(&listen)) {
+                self.listens.push(listen);
+                self.backdated_count += 1;
+            }
+        }
+
+        if self.backdated_count >= self.missing_listens {
+            return true;
+        }
+
+        if self.scanned >= self.scan_limit {
+            self.backdated_scan_truncated = true;
+            return true;
+        }
+
+        false
+    }
+
+    fn finish(self) -> ListenSyncResult {
+        let mut cursor = self.cursor.clone();
+        cursor.advance(&self.listens);
+        cursor.listen_count = Some(self.listen_count);
+
+        ListenSyncResult {
+            listens: self.listens,
+            cursor,
+            backdated_listens_found: self.backdated_count > 0,
+            backdated_scan_truncated: self.backdated_scan_truncated,
+        }
+    }
+}
+
+#[cfg(any(feature = "sync", feature = "async"))]
+#[derive(Debug, snafu::Snafu)]
+pub enum ListenSyncError {
+    ListenFullFetchError {
+        source: crate::api::user::username::listens_reader::ListenFullFetchError,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+
+    ListensSnapshotError {
+        source: crate::api::user::username::listen_count::ListensSnapshotError,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+}
+
+#[cfg(test)]
+mod test {
+    #[cfg(feature = "async")]
+    use macro_rules_attribute::apply;
+
+    use crate::api::user::username::listens::UserListensListen;
+    use crate::api::user::username::listens_sync::ListenSyncCursor;
+
+    const MSID: &str = "cfb002e7-f093-4678-8bf7-fb139a4f718c";
+
+    fn listen(listened_at: i64, inserted_at: i64) -> UserListensListen {
+        listen_with_msid(listened_at, inserted_at, MSID)
+    }
+
+    fn listen_with_msid(listened_at: i64, inserted_at: i64, msid: &str) -> UserListensListen {
+        serde_json::from_value(serde_json::json!({
+            "user_name": "RustyNova",
+            "inserted_at": inserted_at,
+            "listened_at": listened_at,
+            "recording_msid": msid,
+            "track_metadata": {
+                "artist_name": "Kikuo",
+                "track_name": "Aishite Aishite Aishite",
+                "release_name": null,
+                "additional_info": {},
+                "mbid_mapping": null
+            }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn cursor_advance_test() {
+        let mut cursor = ListenSyncCursor::new("RustyNova".to_string());
+        assert!(cursor.is_new(&listen(100, 200)));
+
+        cursor.advance(&[listen(100, 200), listen(300, 250)]);
+        assert_eq!(cursor.latest_listened_at, Some(300));
+        assert_eq!(cursor.latest_inserted_at, Some(250));
+
+        // A backdated import is still new
+        assert!(cursor.is_new(&listen(50, 400)));
+        assert!(!cursor.is_new(&listen(300, 250)));
+
+        // Other listens inserted in the same second as the latest one are still new
+        let same_second = listen_with_msid(280, 250, "8f3471b5-7e6a-48da-86a9-c1c07a0f47ae");
+        assert!(cursor.is_new(&same_second));
+
+        cursor.advance(core::slice::from_ref(&same_second));
+        assert_eq!(cursor.latest_inserted_listens.len(), 2);
+        assert!(!cursor.is_new(&same_second));
+        assert!(!cursor.is_new(&listen(300, 250)));
+
+        // A newer insertion forgets the listens of the previous second
+        cursor.advance(&[listen(400, 300)]);
+        assert_eq!(cursor.latest_inserted_listens.len(), 1);
+    }
+
+    #[cfg(feature = "async")]
+    #[apply(smol_macros::test!)]
+    async fn sync_user_listens_test() {
+        use crate::ListenBrainzAPIEnpoints;
+        use crate::api::submit_listens::SubmitListensPayload;
+        use crate::api::submit_listens::SubmittedListen;
+        use crate::api::submit_listens::SubmittedTrackMetadata;
+        use crate::client::cache::ResponseCache;
+        use crate::mock_server::MockListenBrainzServer;
+        use crate::mock_server::data::FIXTURE_LISTENS_START;
+        use crate::mock_server::data::FIXTURE_TOKEN;
+        use crate::mock_server::data::FIXTURE_USER;
+        use crate::models::token::UserToken;
+
+        let server = MockListenBrainzServer::start_with_fixtures().unwrap();
+        // The sync must still see the new listens with a response cache
+        let client = crate::ListenBrainzClient::builder()
+            .endpoints(server.endpoints())
+            .cache(ResponseCache::builder().build())
+            .build();
+
+        let first = ListenBrainzAPIEnpoints::sync_user_listens()
+            .client(&client)
+            .cursor(&ListenSyncCursor::new(FIXTURE_USER.to_string()))
+            .call()
+            .await
+            .unwrap();
+        let cursor = first.cursor;
+        let latest_listened_at = cursor.latest_listened_at.unwrap();
+        let latest_inserted_at = cursor.latest_inserted_at.unwrap();
+
+        // Nothing changed
+        let unchanged = ListenBrainzAPIEnpoints::sync_user_listens()
+            .client(&client)
+            .cursor(&cursor)
+            .call()
+            .await
+            .unwrap();
+        assert!(unchanged.listens.is_empty());
+
+        let submitted = |listened_at, track_name: &str| {
+            SubmittedListen::builder()
+                .listened_at(listened_at)
+                .track_metadata(
+                    SubmittedTrackMetadata::builder()
+                        .artist_name("Kikuo")
+                        .track_name(track_name)
+                        .build(),
+                )
+                .build()
+        };
+        let new_listen_ts = latest_listened_at + 1000;
+        let backdated_ts = FIXTURE_LISTENS_START + 1000;
+        client
+            .post_submit_listens_async(
+                SubmitListensPayload::Import(vec![
+                    submitted(new_listen_ts, "New"),
+                    submitted(backdated_ts, "Backdated"),
+                ]),
+                UserToken::from(FIXTURE_TOKEN.to_string()),
+            )
+            .await
+            .unwrap();
+
+        let second = ListenBrainzAPIEnpoints::sync_user_listens()
+            .client(&client)
+            .cursor(&cursor)
+            .call()
+            .await
+            .unwrap();
+
+        let mut listened_ats = second
+            .listens
+            .iter()
+            .map(|listen| listen.listened_at)
+            .collect::<Vec<_>>();
+        listened_ats.sort_unstable();
+        assert_eq!(listened_ats, [backdated_ts, new_listen_ts]);
+        assert!(second.backdated_listens_found);
+        assert!(!second.backdated_scan_truncated);
+
+        assert_eq!(second.cursor.latest_listened_at, Some(new_listen_ts));
+        assert!(second.cursor.latest_inserted_at.unwrap() > latest_inserted_at);
+        assert_eq!(
+            second.cursor.listen_count,
+            cursor.listen_count.map(|count| count + 2)
+        );
+    }
+
+    #[cfg(feature = "sync")]
+    #[test]
+    fn sync_user_listens_blocking_test() {
+        use crate::ListenBrainzAPIEnpoints;
+        use crate::api::submit_listens::SubmitListensPayload;
+        use crate::api::submit_listens::SubmittedListen;
+        use crate::api::submit_listens::SubmittedTrackMetadata;
+        use crate::client::cache::ResponseCache;
+        use crate::mock_server::MockListenBrainzServer;
+        use crate::mock_server::data::FIXTURE_LISTENS_START;
+        use crate::mock_server::data::FIXTURE_TOKEN;
+        use crate::mock_server::data::FIXTURE_USER;
+        use crate::models::token::UserToken;
+
+        let server = MockListenBrainzServer::start_with_fixtures().unwrap();
+        let client = crate::ListenBrainzClient::builder()
+            .endpoints(server.endpoints())
+            .cache(ResponseCache::builder().build())
+            .build();
+
+        let first = ListenBrainzAPIEnpoints::sync_user_listens_blocking()
+            .client(&client)
+            .cursor(&ListenSyncCursor::new(FIXTURE_USER.to_string()))
+            .call()
+            .unwrap();
+
+        let backdated_ts = FIXTURE_LISTENS_START + 1000;
+        client
+            .post_submit_listens(
+                SubmitListensPayload::Import(vec![
+                    SubmittedListen::builder()
+                        .listened_at(backdated_ts)
+                        .track_metadata(
+                            SubmittedTrackMetadata::builder()
+                                .artist_name("Kikuo")
+                                .track_name("Backdated")
+                                .build(),
+                        )
+                        .build(),
+                ]),
+                UserToken::from(FIXTURE_TOKEN.to_string()),
+            )
+            .unwrap();
+
+        let second = ListenBrainzAPIEnpoints::sync_user_listens_blocking()
+            .client(&client)
+            .cursor(&first.cursor)
+            .call()
+            .unwrap();
+
+        let listened_ats = second
+            .listens
+            .iter()
+            .map(|listen| listen.listened_at)
+            .collect::<Vec<_>>();
+        assert_eq!(listened_ats, [backdated_ts]);
+        assert!(second.backdated_listens_found);
+        assert_eq!(
+            second.cursor.listen_count,
+            first.cursor.listen_count.map(|count| count + 1)
+        );
+    }
+}
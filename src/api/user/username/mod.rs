@@ -1,4 +1,7 @@
 pub mod fresh_releases;
+pub mod listen_count;
 pub mod listens;
-#[cfg(feature = "async")]
+#[cfg(any(feature = "sync", feature = "async"))]
 pub mod listens_reader;
+pub mod listens_sync;
+pub mod playing_now;
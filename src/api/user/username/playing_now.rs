@@ -0,0 +1,99 @@
+use api_bindium::ApiRequest;
+use api_bindium::HTTPVerb;
+use api_bindium::endpoints::UriBuilderError;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::api::ListenBrainzAPIEnpoints;
+use crate::api::user::username::listens::UserListensTrackMetadata;
+use crate::parser::ListenBrainzParser;
+
+impl ListenBrainzAPIEnpoints {
+    /// Get the track the user is currently listening to
+    pub fn get_user_username_playing_now(
+        &self,
+        username: &str,
+    ) -> Result<ApiRequest<ListenBrainzParser<UserPlayingNowResponse>>, UriBuilderError> {
+        self.endpoint_builder()
+            .set_path(&format!("/1/user/{username}/playing-now"))
+            .into_api_request(HTTPVerb::Get, ListenBrainzParser::default())
+    }
+}
+
+// === Response types ===
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct UserPlayingNowResponse {
+    pub payload: UserPlayingNowPayload,
+}
+
+/// Type of the [`UserPlayingNowResponse::payload`] field.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct UserPlayingNowPayload {
+    pub count: u64,
+    pub user_id: String,
+    pub playing_now: bool,
+    pub listens: Vec<UserPlayingNowListen>,
+}
+
+impl UserPlayingNowPayload {
+    /// Return the track currently playing, if any
+    pub fn current_track(&self) -> Option<&UserListensTrackMetadata> {
+        self.listens.first().map(|listen| &listen.track_metadata)
+    }
+}
+
+/// Type of the [`UserPlayingNowPayload::listens`] field.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct UserPlayingNowListen {
+    pub playing_now: bool,
+    pub track_metadata: UserListensTrackMetadata,
+}
+
+#[cfg(test)]
+#[cfg(feature = "async")]
+mod test {
+    use macro_rules_attribute::apply;
+
+    use crate::api::submit_listens::SubmittedTrackMetadata;
+    use crate::mock_server::MockListenBrainzServer;
+    use crate::mock_server::data::FIXTURE_TOKEN;
+    use crate::mock_server::data::FIXTURE_USER;
+    use crate::models::token::UserToken;
+
+    #[apply(smol_macros::test!)]
+    async fn get_user_username_playing_now_test() {
+        let server = MockListenBrainzServer::start_with_fixtures().unwrap();
+        let client = server.client();
+
+        let res = client
+            .get_user_username_playing_now_async(FIXTURE_USER)
+            .await
+            .unwrap();
+        assert_eq!(res.payload.user_id, FIXTURE_USER);
+        assert_eq!(res.payload.current_track(), None);
+
+        client
+            .post_submit_playing_now_async(
+                SubmittedTrackMetadata::builder()
+                    .artist_name("Nightwish")
+                    .track_name("Ghost Love Score")
+                    .release_name("Once")
+                    .build(),
+                UserToken::from(FIXTURE_TOKEN.to_string()),
+            )
+            .await
+            .unwrap();
+
+        let res = client
+            .get_user_username_playing_now_async(FIXTURE_USER)
+            .await
+            .unwrap();
+        assert!(res.payload.playing_now);
+
+        let track = res.payload.current_track().unwrap();
+        assert_eq!(track.artist_name, "Nightwish");
+        assert_eq!(track.track_name, "Ghost Love Score");
+        assert_eq!(track.release_name.as_deref(), Some("Once"));
+    }
+}
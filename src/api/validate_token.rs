@@ -1,26 +1,25 @@
-use api_bindium::ApiRequest;
 use api_bindium::HTTPVerb;
-use api_bindium::JsonParser;
 use api_bindium::endpoints::UriBuilderError;
 use serde::Deserialize;
 use serde::Serialize;
 
 use crate::api::ListenBrainzAPIEnpoints;
+use crate::models::token::AuthenticatedRequest;
 use crate::models::token::UserToken;
+use crate::parser::ListenBrainzParser;
 
 impl ListenBrainzAPIEnpoints {
     pub fn post_validate_token(
         &self,
         token: UserToken,
-    ) -> Result<ApiRequest<JsonParser<ValidateTokenResponse>>, UriBuilderError> {
-        let mut request = self
+    ) -> Result<AuthenticatedRequest<ListenBrainzParser<ValidateTokenResponse>>, UriBuilderError>
+    {
+        let request = self
             .endpoint_builder()
             .set_path("/1/validate-token")
-            .into_api_request(HTTPVerb::Get, JsonParser::default())?;
+            .into_api_request(HTTPVerb::Get, ListenBrainzParser::default())?;
 
-        token.add_authorization(&mut request);
-
-        Ok(request)
+        Ok(AuthenticatedRequest::new(request, token))
     }
 }
 
@@ -29,5 +28,55 @@ pub struct ValidateTokenResponse {
     pub code: u32,
     pub message: String,
     pub valid: bool,
-    pub user_name: String,
+
+    /// The user owning the token. ListenBrainz leaves it out if the token is invalid
+    #[serde(default)]
+    pub user_name: Option<String>,
+}
+
+#[cfg(test)]
+mod test {
+    #[cfg(feature = "async")]
+    use macro_rules_attribute::apply;
+
+    use crate::api::validate_token::ValidateTokenResponse;
+
+    #[test]
+    fn invalid_token_response_test() {
+        // The body returned by ListenBrainz for an invalid token
+        let res = serde_json::from_str::<ValidateTokenResponse>(
+            r#"{"code": 200, "message": "Token invalid.", "valid": false}"#,
+        )
+        .unwrap();
+
+        assert!(!res.valid);
+        assert_eq!(res.user_name, None);
+    }
+
+    #[cfg(feature = "async")]
+    #[apply(smol_macros::test!)]
+    async fn verify_invalid_token_test() {
+        use crate::error::Error;
+        use crate::error::ErrorCategory;
+        use crate::mock_server::MockListenBrainzServer;
+        use crate::mock_server::data::FIXTURE_TOKEN;
+        use crate::mock_server::data::FIXTURE_USER;
+        use crate::models::token::UserToken;
+
+        let server = MockListenBrainzServer::start_with_fixtures().unwrap();
+        let client = server.client();
+
+        let res = UserToken::from(FIXTURE_TOKEN.to_string())
+            .verify_async(&client)
+            .await
+            .unwrap();
+        assert_eq!(res.user_name.as_deref(), Some(FIXTURE_USER));
+
+        let err: Error = UserToken::from("wrong".to_string())
+            .verify_async(&client)
+            .await
+            .unwrap_err()
+            .into();
+        assert_eq!(err.category(), ErrorCategory::Authentication);
+    }
 }
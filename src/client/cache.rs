@@ -0,0 +1,584 @@
+use core::time::Duration;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use api_bindium::ApiRequest;
+use api_bindium::ApiRequestError;
+use api_bindium::api_response::ureq_response::UreqResponse;
+use chrono::Utc;
+use serde::Deserialize;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use snafu::ResultExt as _;
+use snafu::Snafu;
+
+use crate::client::stored_response::StoredResponse;
+use crate::parser::ListenBrainzParser;
+
+/// How long the responses are kept by default
+pub const DEFAULT_CACHE_TTL: Duration = Duration::from_secs(60);
+
+/// The number of responses kept in memory by default
+pub const DEFAULT_CACHE_CAPACITY: usize = 256;
+
+/// A cache of the responses of the server.
+///
+/// Only the requests that don't need a token are cached, so submissions and other user actions always reach the server.
+/// The responses are kept in memory in a LRU cache, and optionally in a directory to keep them between runs.
+///
+/// Once a response is expired, it's revalidated with `If-None-Match` if the server gave it an `ETag`, and reused if it didn't change.
+///
+/// Set it on a client with [`crate::ListenBrainzClient::builder`]. Use [`crate::ListenBrainzClient::bypassing_cache`] to get the latest data.
+#[derive(Debug, Clone)]
+pub struct ResponseCache(Arc<ResponseCacheInner>);
+
+#[bon::bon]
+impl ResponseCache {
+    #[builder]
+    pub fn new(
+        /// The maximum number of responses kept in memory. The least recently used ones are dropped first
+        #[builder(default = DEFAULT_CACHE_CAPACITY)]
+        capacity: usize,
+        /// Also store the responses in this directory. It should only be used by the cache
+        #[builder(into)]
+        disk_dir: Option<PathBuf>,
+        /// How long the responses are kept if their endpoint doesn't have its own ttl
+        #[builder(default = DEFAULT_CACHE_TTL)]
+        default_ttl: Duration,
+        /// The ttl of the responses of specific endpoints, keyed by their path. `*` matches any path segment (ex: `/1/user/*/listens`).
+        ///
+        /// A ttl of zero disables the caching of the endpoint
+        #[builder(default)]
+        endpoint_ttls: HashMap<String, Duration>,
+    ) -> Self {
+        Self(Arc::new(ResponseCacheInner {
+            capacity,
+            disk_dir,
+            default_ttl,
+            endpoint_ttls,
+            memory: Mutex::new(MemoryCache::default()),
+        }))
+    }
+
+    /// Remove all the responses from the cache, including the ones on disk
+    pub fn clear(&self) -> Result<(), CacheError> {
+        *self.0.memory() = MemoryCache::default();
+
+        let Some(dir) = &self.0.disk_dir else {
+            return Ok(());
+        };
+
+        let entries = match std::fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(source) => return Err(source).context(ClearDiskSnafu { path: dir }),
+        };
+
+        for entry in entries {
+            let path = entry.context(ClearDiskSnafu { path: dir })?.path();
+
+            if path.extension().is_some_and(|ext| ext == "json") {
+                std::fs::remove_file(&path).context(ClearDiskSnafu { path })?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Return how long the response of the request should be kept, or `None` if it mustn't be cached
+    fn ttl<P>(&self, request: &ApiRequest<P>) -> Option<Duration> {
+        let authenticated = request
+            .headers()
+            .keys()
+            .any(|name| name.eq_ignore_ascii_case("authorization"));
+        if authenticated {
+            return None;
+        }
+
+        let path = request.uri().path();
+        let ttl = self
+            .0
+            .endpoint_ttls
+            .iter()
+            .filter(|(pattern, _)| endpoint_matches(pattern, path))
+            // Prefer the most specific pattern
+            .max_by_key(|(pattern, _)| (pattern.len(), pattern.as_str()))
+            .map_or(self.0.default_ttl, |(_, ttl)| *ttl);
+
+        (!ttl.is_zero()).then_some(ttl)
+    }
+
+    /// Look for the response of the request in the cache.
+    ///
+    /// If it's expired but has an ETag, the request is modified to revalidate it
+    pub(crate) fn lookup<T>(
+        &self,
+        request: &mut ApiRequest<ListenBrainzParser<T>>,
+        bypass: bool,
+    ) -> Result<CacheLookup<T>, ApiRequestError>
+    where
+        T: DeserializeOwned,
+    {
+        let Some(ttl) = self.ttl(request) else {
+            return Ok(CacheLookup::Uncached);
+        };
+        let key = cache_key(request);
+
+        let stale = match self.0.get(&key).filter(|_| !bypass) {
+            Some(entry) if entry.is_fresh() => {
+                return entry
+                    .response
+                    .to_ureq_response(request)
+                    .map(CacheLookup::Hit);
+            }
+            Some(entry) => entry.etag.is_some().then_some(entry),
+            None => None,
+        };
+
+        if let Some(etag) = stale.as_ref().and_then(|entry| entry.etag.clone()) {
+            request
+                .headers_mut()
+                .insert(IF_NONE_MATCH.to_string(), etag);
+        }
+
+        Ok(CacheLookup::Miss(PendingEntry { key, ttl, stale }))
+    }
+
+    /// Store the response of the server. If the server confirmed that the stale response didn't change, it's returned instead
+    pub(crate) fn store<T>(
+        &self,
+        request: &mut ApiRequest<ListenBrainzParser<T>>,
+        mut response: UreqResponse<ListenBrainzParser<T>>,
+        pending: PendingEntry,
+    ) -> Result<UreqResponse<ListenBrainzParser<T>>, ApiRequestError>
+    where
+        T: DeserializeOwned,
+    {
+        request.headers_mut().remove(IF_NONE_MATCH);
+        let status = response.inner.data.status();
+
+        if let (304, Some(mut stale)) = (status.as_u16(), pending.stale) {
+            stale.refresh(pending.ttl);
+            let response = stale.response.to_ureq_response(request)?;
+            self.0.insert(stale);
+
+            return Ok(response);
+        }
+
+        if !status.is_success() {
+            return Ok(response);
+        }
+
+        let stored = StoredResponse::read(request, &mut response)?;
+        let mut entry = CacheEntry {
+            key: pending.key,
+            etag: stored.header("etag").map(ToString::to_string),
+            response: stored,
+            stored_at_ms: 0,
+            ttl_ms: 0,
+        };
+        entry.refresh(pending.ttl);
+        self.0.insert(entry);
+
+        Ok(response)
+    }
+}
+
+const IF_NONE_MATCH: &str = "If-None-Match";
+
+/// The result of [`ResponseCache::lookup`]
+pub(crate) enum CacheLookup<T>
+where
+    T: DeserializeOwned,
+{
+    /// A fresh response is in the cache
+    Hit(UreqResponse<ListenBrainzParser<T>>),
+
+    /// The request must be sent, and its response stored with [`ResponseCache::store`]
+    Miss(PendingEntry),
+
+    /// The request can't be cached
+    Uncached,
+}
+
+/// A response waiting to be stored in the cache
+pub(crate) struct PendingEntry {
+    key: String,
+    ttl: Duration,
+
+    /// The expired response, if the server is asked whether it changed
+    stale: Option<CacheEntry>,
+}
+
+impl PendingEntry {
+    /// Remove the `If-None-Match` header added by [`ResponseCache::lookup`], if the request failed before it could be stored
+    pub(crate) fn discard<P>(self, request: &mut ApiRequest<P>) {
+        if self.stale.is_some() {
+            request.headers_mut().remove(IF_NONE_MATCH);
+        }
+    }
+}
+
+#[derive(Debug)]
+struct ResponseCacheInner {
+    capacity: usize,
+    disk_dir: Option<PathBuf>,
+    default_ttl: Duration,
+    endpoint_ttls: HashMap<String, Duration>,
+    memory: Mutex<MemoryCache>,
+}
+
+impl ResponseCacheInner {
+    fn memory(&self) -> std::sync::MutexGuard<'_, MemoryCache> {
+        self.memory.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    fn get(&self, key: &str) -> Option<CacheEntry> {
+        if let Some(entry) = self.memory().get(key) {
+            return Some(entry);
+        }
+
+        let content = std::fs::read_to_string(self.disk_path(key)?).ok()?;
+        let entry = serde_json::from_str::<CacheEntry>(&content)
+            .ok()
+            .filter(|entry| entry.key == key)?;
+        self.memory().insert(entry.clone(), self.capacity);
+
+        Some(entry)
+    }
+
+    fn insert(&self, entry: CacheEntry) {
+        // The cache is only an optimisation. Failing to write it isn't worth failing the request
+        if let (Some(path), Ok(content)) =
+            (self.disk_path(&entry.key), serde_json::to_string(&entry))
+        {
+            let _ = std::fs::create_dir_all(path.parent().unwrap_or(&path))
+                .and_then(|()| std::fs::write(&path, content));
+        }
+
+        self.memory().insert(entry, self.capacity);
+    }
+
+    fn disk_path(&self, key: &str) -> Option<PathBuf> {
+        Some(
+            self.disk_dir
+                .as_ref()?
+                .join(format!("{:016x}.json", fnv1a(key))),
+        )
+    }
+}
+
+/// A LRU cache of the responses
+#[derive(Debug, Default)]
+struct MemoryCache {
+    /// The entries, with the last time they got used
+    entries: HashMap<String, (CacheEntry, u64)>,
+    clock: u64,
+}
+
+impl MemoryCache {
+    fn get(&mut self, key: &str) -> Option<CacheEntry> {
+        self.clock += 1;
+        let (entry, last_used) = self.entries.get_mut(key)?;
+        *last_used = self.clock;
+
+        Some(entry.clone())
+    }
+
+    fn insert(&mut self, entry: CacheEntry, capacity: usize) {
+        if capacity == 0 {
+            return;
+        }
+
+        if !self.entries.contains_key(&entry.key) && self.entries.len() >= capacity {
+            let oldest = self
+                .entries
+                .iter()
+                .min_by_key(|(_, (_, last_used))| *last_used)
+                .map(|(key, _)| key.clone());
+
+            if let Some(oldest) = oldest {
+                self.entries.remove(&oldest);
+            }
+        }
+
+        self.clock += 1;
+        self.entries.insert(entry.key.clone(), (entry, self.clock));
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CacheEntry {
+    key: String,
+    response: StoredResponse,
+    etag: Option<String>,
+
+    /// When the response got stored or revalidated, in milliseconds since the UNIX epoch
+    stored_at_ms: i64,
+    ttl_ms: u64,
+}
+
+impl CacheEntry {
+    fn is_fresh(&self) -> bool {
+        let expires_at = self.stored_at_ms.saturating_add_unsigned(self.ttl_ms);
+
+        Utc::now().timestamp_millis() < expires_at
+    }
+
+    /// Mark the response as fresh for `ttl`
+    fn refresh(&mut self, ttl: Duration) {
+        self.stored_at_ms = Utc::now().timestamp_millis();
+        self.ttl_ms = u64::try_from(ttl.as_millis()).unwrap_or(u64::MAX);
+    }
+}
+
+/// The key of a request in the cache
+fn cache_key<P>(request: &ApiRequest<P>) -> String {
+    let body = request
+        .body()
+        .as_ref()
+        .map(ToString::to_string)
+        .unwrap_or_default();
+
+    format!("{} {} {body}", request.verb(), request.uri())
+}
+
+/// Return true if the path ends with the segments of the pattern. `*` matches any segment
+pub(crate) fn endpoint_matches(pattern: &str, path: &str) -> bool {
+    let pattern = pattern.trim_matches('/').split('/').collect::<Vec<_>>();
+    let path = path.trim_matches('/').split('/').collect::<Vec<_>>();
+
+    // The path may have the prefix of a self-hosted server
+    let Some(offset) = path.len().checked_sub(pattern.len()) else {
+        return false;
+    };
+
+    pattern
+        .iter()
+        .zip(&path[offset..])
+        .all(|(pattern, segment)| *pattern == "*" || pattern == segment)
+}
+
+/// A hash that stays the same between runs and rust versions, to name the files of the disk cache
+fn fnv1a(value: &str) -> u64 {
+    value.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+#[derive(Debug, Snafu)]
+pub enum CacheError {
+    #[snafu(display("Couldn't clear the cache directory `{}`", path.display()))]
+    ClearDiskError {
+        source: std::io::Error,
+        path: PathBuf,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+}
+
+#[cfg(test)]
+#[cfg(feature = "async")]
+mod test {
+    use core::time::Duration;
+    use std::collections::HashMap;
+
+    use macro_rules_attribute::apply;
+
+    use crate::client::ListenBrainzClient;
+    use crate::client::cache::ResponseCache;
+    use crate::client::cache::endpoint_matches;
+    use crate::client::cassette::Cassette;
+    use crate::client::cassette::CassetteMode;
+    use crate::client::retry::RetryPolicy;
+    use crate::mock_server::MockListenBrainzServer;
+    use crate::mock_server::data::FIXTURE_USER;
+
+    #[test]
+    fn endpoint_matches_test() {
+        assert!(endpoint_matches(
+            "/1/user/*/listens",
+            "/1/user/RustyNova/listens"
+        ));
+        assert!(endpoint_matches(
+            "/1/user/*/listens",
+            "/prefix/1/user/RustyNova/listens"
+        ));
+        assert!(!endpoint_matches(
+            "/1/user/*/listens",
+            "/1/user/RustyNova/listen-count"
+        ));
+        assert!(!endpoint_matches(
+            "/1/user/*/listens",
+            "/user/RustyNova/listens"
+        ));
+    }
+
+    #[apply(smol_macros::test!)]
+    async fn cache_test() {
+        let server = MockListenBrainzServer::start_with_fixtures().unwrap();
+        let disk_dir =
+            std::env::temp_dir().join(format!("listenbrainz_rs_cache_{}", std::process::id()));
+
+        let cache = ResponseCache::builder()
+            .disk_dir(&disk_dir)
+            .endpoint_ttls(HashMap::from([
+                ("/1/user/*/playing-now".to_string(), Duration::ZERO),
+                // Expires right away, so it's revalidated on each request
+                ("/1/user/*/listens".to_string(), Duration::from_millis(1)),
+            ]))
+            .build();
+        let client = ListenBrainzClient::builder()
+            .endpoints(server.endpoints())
+            .cache(cache.clone())
+            .build();
+
+        let count = client
+            .get_user_username_listen_count_async(FIXTURE_USER)
+            .await
+            .unwrap();
+        let cached = client
+            .get_user_username_listen_count_async(FIXTURE_USER)
+            .await
+            .unwrap();
+        assert_eq!(cached.payload.count, count.payload.count);
+        assert_eq!(server.request_count(), 1);
+
+        client
+            .bypassing_cache()
+            .get_user_username_listen_count_async(FIXTURE_USER)
+            .await
+            .unwrap();
+        assert_eq!(server.request_count(), 2);
+
+        // Disabled endpoint
+        for _ in 0..2 {
+            client
+                .get_user_username_playing_now_async(FIXTURE_USER)
+                .await
+                .unwrap();
+        }
+        assert_eq!(server.request_count(), 4);
+
+        // Revalidated with the ETag. The server answers with a `304 Not Modified`, and the stored body is returned
+        let mut responses = Vec::new();
+        for _ in 0..2 {
+            async_io::Timer::after(Duration::from_millis(5)).await;
+
+            let res = client
+                .get_user_username_listens_async()
+                .username(FIXTURE_USER)
+                .count(5)
+                .call()
+                .await
+                .unwrap();
+            responses.push(res.payload.listens);
+        }
+        assert_eq!(server.request_count(), 6);
+        assert_eq!(responses[0].len(), 5);
+        assert_eq!(responses[0], responses[1]);
+
+        // A new cache reads the responses stored on disk
+        let client = ListenBrainzClient::builder()
+            .endpoints(server.endpoints())
+            .cache(ResponseCache::builder().disk_dir(&disk_dir).build())
+            .build();
+        client
+            .get_user_username_listen_count_async(FIXTURE_USER)
+            .await
+            .unwrap();
+        assert_eq!(server.request_count(), 6);
+
+        cache.clear().unwrap();
+        std::fs::remove_dir(&disk_dir).unwrap();
+    }
+
+    #[apply(smol_macros::test!)]
+    async fn failed_revalidation_test() {
+        let server = MockListenBrainzServer::start_with_fixtures().unwrap();
+        let client = ListenBrainzClient::builder()
+            .endpoints(server.endpoints())
+            .cache(
+                ResponseCache::builder()
+                    .default_ttl(Duration::from_millis(1))
+                    .build(),
+            )
+            .retry_policy(RetryPolicy::none())
+            .build();
+        let mut request = client
+            .endpoints()
+            .get_user_username_listen_count(FIXTURE_USER)
+            .unwrap();
+
+        client.send_async(&mut request).await.unwrap();
+        async_io::Timer::after(Duration::from_millis(5)).await;
+
+        // The revalidation fails, so the request is left as the caller gave it
+        server.data().queued_errors.push_back(503);
+        client.send_async(&mut request).await.unwrap_err();
+        assert!(
+            !request
+                .headers()
+                .keys()
+                .any(|name| name.eq_ignore_ascii_case("if-none-match"))
+        );
+    }
+
+    #[apply(smol_macros::test!)]
+    async fn cache_with_cassette_test() {
+        let server = MockListenBrainzServer::start_with_fixtures().unwrap();
+        let disk_dir = std::env::temp_dir().join(format!(
+            "listenbrainz_rs_cache_cassette_{}",
+            std::process::id()
+        ));
+        let cassette_path = disk_dir.with_extension("json");
+
+        // Fill the disk cache
+        let cache = ResponseCache::builder().disk_dir(&disk_dir).build();
+        let client = ListenBrainzClient::builder()
+            .endpoints(server.endpoints())
+            .cache(cache.clone())
+            .build();
+        let count = client
+            .get_user_username_listen_count_async(FIXTURE_USER)
+            .await
+            .unwrap();
+
+        // Record a cache hit
+        let cassette = Cassette::open(&cassette_path, CassetteMode::Record).unwrap();
+        let client = ListenBrainzClient::builder()
+            .endpoints(server.endpoints())
+            .cache(ResponseCache::builder().disk_dir(&disk_dir).build())
+            .cassette(cassette.clone())
+            .build();
+        client
+            .get_user_username_listen_count_async(FIXTURE_USER)
+            .await
+            .unwrap();
+        assert_eq!(server.request_count(), 1);
+        cassette.save().unwrap();
+
+        // Replay it without the cache nor the server
+        let endpoints = server.endpoints();
+        drop(server);
+        let client = ListenBrainzClient::builder()
+            .endpoints(endpoints)
+            .cassette(Cassette::open(&cassette_path, CassetteMode::Replay).unwrap())
+            .build();
+        let replayed = client
+            .get_user_username_listen_count_async(FIXTURE_USER)
+            .await
+            .unwrap();
+        assert_eq!(replayed.payload.count, count.payload.count);
+
+        cache.clear().unwrap();
+        std::fs::remove_file(&cassette_path).unwrap();
+        std::fs::remove_dir(&disk_dir).unwrap();
+    }
+}
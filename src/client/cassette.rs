@@ -0,0 +1,370 @@
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use api_bindium::ApiRequest;
+use api_bindium::ApiRequestError;
+use api_bindium::api_response::ureq_response::UreqResponse;
+use api_bindium::ureq;
+use serde::Deserialize;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use snafu::ResultExt as _;
+use snafu::Snafu;
+
+use crate::client::stored_response::StoredResponse;
+use crate::parser::ListenBrainzParser;
+use crate::parser::ureq_error;
+
+/// What a [`Cassette`] does with the requests of the client
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CassetteMode {
+    /// Send the requests to the server, and record their responses. A previous recording is replaced
+    Record,
+
+    /// Never send the requests, and answer them with the recorded responses. Requests without a recording fail
+    Replay,
+
+    /// Replay the cassette if its file exists, or record it otherwise
+    Once,
+}
+
+/// Record the responses of the server to disk, and replay them later without network access.
+///
+/// The recordings are keyed on the method, path, query and body of the requests. Headers aren't recorded, so tokens don't leak into the cassette.
+/// When the same request got recorded multiple times, the responses are replayed in their recording order.
+///
+/// Set it on a client with [`crate::ListenBrainzClient::builder`]. The recording is written to disk when [`Cassette::save`] is called,
+/// or when the last clone of the cassette is dropped.
+///
+/// Please note that concurrent requests (ex: parallel listen fetching) may be sent in a different order, and create different requests.
+#[derive(Debug, Clone)]
+pub struct Cassette(Arc<CassetteInner>);
+
+impl Cassette {
+    /// Open the cassette file at `path`
+    pub fn open(path: impl Into<PathBuf>, mode: CassetteMode) -> Result<Self, CassetteError> {
+        let path = path.into();
+        let recording = match mode {
+            CassetteMode::Record => true,
+            CassetteMode::Replay => false,
+            CassetteMode::Once => !path.exists(),
+        };
+
+        let interactions = if recording {
+            Vec::new()
+        } else {
+            let content = std::fs::read_to_string(&path).context(ReadFileSnafu { path: &path })?;
+            serde_json::from_str::<CassetteFile>(&content)
+                .context(JsonSnafu { path: &path })?
+                .interactions
+        };
+
+        Ok(Self(Arc::new(CassetteInner {
+            path,
+            recording,
+            state: Mutex::new(CassetteState {
+                replayed: vec![false; interactions.len()],
+                interactions,
+                modified: false,
+            }),
+        })))
+    }
+
+    /// The file of the cassette
+    pub fn path(&self) -> &Path {
+        &self.0.path
+    }
+
+    /// Return true if the requests are sent to the server and recorded, or false if they are replayed
+    pub fn is_recording(&self) -> bool {
+        self.0.recording
+    }
+
+    /// Write the recorded responses to the cassette file
+    pub fn save(&self) -> Result<(), CassetteError> {
+        self.0.save()
+    }
+
+    /// Answer the request with its recorded response
+    pub(crate) fn replay<T>(
+        &self,
+        request: &ApiRequest<ListenBrainzParser<T>>,
+    ) -> Result<UreqResponse<ListenBrainzParser<T>>, ApiRequestError>
+    where
+        T: DeserializeOwned,
+    {
+        let key = RecordedRequest::new(request);
+        let mut state = self.0.lock();
+
+        let matches = state
+            .interactions
+            .iter()
+            .enumerate()
+            .filter(|(_, interaction)| interaction.request == key)
+            .map(|(i, _)| i)
+            .collect::<Vec<_>>();
+
+        // Replay the responses in order, then keep replaying the last one
+        let Some(index) = matches
+            .iter()
+            .find(|i| !state.replayed[**i])
+            .or_else(|| matches.last())
+            .copied()
+        else {
+            let source = std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!(
+                    "No response got recorded for `{key}` in the cassette `{}`",
+                    self.0.path.display()
+                ),
+            );
+            return Err(ureq_error(request.uri().clone(), ureq::Error::Io(source)));
+        };
+
+        state.replayed[index] = true;
+        state.interactions[index].response.to_ureq_response(request)
+    }
+
+    /// Record the response of the request, and return a copy of it
+    pub(crate) fn record<T>(
+        &self,
+        request: &ApiRequest<ListenBrainzParser<T>>,
+        mut response: UreqResponse<ListenBrainzParser<T>>,
+    ) -> Result<UreqResponse<ListenBrainzParser<T>>, ApiRequestError>
+    where
+        T: DeserializeOwned,
+    {
+        let recorded = StoredResponse::read(request, &mut response)?;
+
+        let mut state = self.0.lock();
+        state.interactions.push(Interaction {
+            request: RecordedRequest::new(request),
+            response: recorded,
+        });
+        state.replayed.push(true);
+        state.modified = true;
+
+        Ok(response)
+    }
+}
+
+#[derive(Debug)]
+struct CassetteInner {
+    path: PathBuf,
+    recording: bool,
+    state: Mutex<CassetteState>,
+}
+
+impl CassetteInner {
+    fn lock(&self) -> std::sync::MutexGuard<'_, CassetteState> {
+        self.state.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    fn save(&self) -> Result<(), CassetteError> {
+        let mut state = self.lock();
+        let file = CassetteFile {
+            interactions: state.interactions.clone(),
+        };
+
+        let content =
+            serde_json::to_string_pretty(&file).context(JsonSnafu { path: &self.path })?;
+
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent).context(WriteFileSnafu { path: &self.path })?;
+        }
+        std::fs::write(&self.path, content).context(WriteFileSnafu { path: &self.path })?;
+
+        state.modified = false;
+        Ok(())
+    }
+}
+
+impl Drop for CassetteInner {
+    fn drop(&mut self) {
+        if self.recording && self.lock().modified {
+            // Nothing to do with the error here. Call `Cassette::save` to handle it
+            let _ = self.save();
+        }
+    }
+}
+
+#[derive(Debug)]
+struct CassetteState {
+    interactions: Vec<Interaction>,
+
+    /// Whether each interaction got replayed already
+    replayed: Vec<bool>,
+
+    /// Whether some interactions haven't been written to disk yet
+    modified: bool,
+}
+
+// === File format ===
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CassetteFile {
+    interactions: Vec<Interaction>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Interaction {
+    request: RecordedRequest,
+    response: StoredResponse,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct RecordedRequest {
+    method: String,
+    path: String,
+    query: Option<String>,
+    body: Option<serde_json::Value>,
+}
+
+impl RecordedRequest {
+    fn new<P>(request: &ApiRequest<P>) -> Self {
+        Self {
+            method: request.verb().to_string(),
+            path: request.uri().path().to_string(),
+            query: request.uri().query().map(ToString::to_string),
+            body: request.body().clone(),
+        }
+    }
+}
+
+impl core::fmt::Display for RecordedRequest {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{} {}", self.method, self.path)?;
+
+        if let Some(query) = &self.query {
+            write!(f, "?{query}")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Snafu)]
+pub enum CassetteError {
+    #[snafu(display("Couldn't read the cassette `{}`", path.display()))]
+    ReadFileError {
+        source: std::io::Error,
+        path: PathBuf,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+
+    #[snafu(display("Couldn't write the cassette `{}`", path.display()))]
+    WriteFileError {
+        source: std::io::Error,
+        path: PathBuf,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+
+    #[snafu(display("The cassette `{}` isn't valid", path.display()))]
+    JsonError {
+        source: serde_json::Error,
+        path: PathBuf,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+}
+
+/// A client answering from the cassette `fixtures/cassettes/{name}.json`, recorded from ListenBrainz.
+///
+/// Set the `LISTENBRAINZ_RS_RECORD_CASSETTES` environment variable to record the cassettes again from api.listenbrainz.org:
+/// `LISTENBRAINZ_RS_RECORD_CASSETTES=1 cargo test --all-features cassette`
+#[cfg(test)]
+#[cfg(feature = "async")]
+pub(crate) fn fixture_cassette_client(name: &str) -> crate::ListenBrainzClient {
+    let path = format!(
+        "{}/fixtures/cassettes/{name}.json",
+        env!("CARGO_MANIFEST_DIR")
+    );
+    let mode = if std::env::var_os("LISTENBRAINZ_RS_RECORD_CASSETTES").is_some() {
+        CassetteMode::Record
+    } else {
+        CassetteMode::Replay
+    };
+
+    // The recording is saved once the client is dropped
+    crate::ListenBrainzClient::builder()
+        .cassette(Cassette::open(path, mode).unwrap())
+        .build()
+}
+
+#[cfg(test)]
+#[cfg(feature = "async")]
+mod test {
+    use macro_rules_attribute::apply;
+
+    use crate::api::ListenBrainzAPIEnpoints;
+    use crate::client::ListenBrainzClient;
+    use crate::client::cassette::Cassette;
+    use crate::client::cassette::CassetteMode;
+    use crate::mock_server::MockListenBrainzServer;
+
+    #[apply(smol_macros::test!)]
+    async fn record_and_replay_test() {
+        let path = std::env::temp_dir().join(format!(
+            "listenbrainz_rs_cassette_{}.json",
+            std::process::id()
+        ));
+
+        let server = MockListenBrainzServer::start_with_fixtures().unwrap();
+        let cassette = Cassette::open(&path, CassetteMode::Record).unwrap();
+        let client = ListenBrainzClient::builder()
+            .endpoints(server.endpoints())
+            .cassette(cassette.clone())
+            .build();
+
+        let recorded = ListenBrainzAPIEnpoints::get_user_username_listens_full()
+            .client(&client)
+            .username("RustyNova")
+            .start(1_705_000_000_i64)
+            .end(1_710_000_000_i64)
+            .call()
+            .await
+            .unwrap();
+        cassette.save().unwrap();
+
+        // Replay without the server
+        let endpoints = server.endpoints();
+        drop(server);
+        let client = ListenBrainzClient::builder()
+            .endpoints(endpoints)
+            .cassette(Cassette::open(&path, CassetteMode::Once).unwrap())
+            .build();
+
+        let replayed = ListenBrainzAPIEnpoints::get_user_username_listens_full()
+            .client(&client)
+            .username("RustyNova")
+            .start(1_705_000_000_i64)
+            .end(1_710_000_000_i64)
+            .call()
+            .await
+            .unwrap();
+        assert_eq!(replayed.listens, recorded.listens);
+
+        let missing = client
+            .get_user_username_listen_count_async("RustyNova")
+            .await;
+        assert!(missing.is_err());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
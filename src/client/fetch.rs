@@ -0,0 +1,355 @@
+use api_bindium::ApiRequest;
+use serde::de::DeserializeOwned;
+
+use crate::api::delete_listen::DeleteListenBody;
+use crate::api::delete_listen::DeleteListenResponse;
+use crate::api::metadata::submit_manual_mapping::SubmitManualMappingBody;
+use crate::api::popularity::recordings::PopularityRecordingResponse;
+use crate::api::submit_listens::SubmitListensPayload;
+use crate::api::submit_listens::SubmitListensResponse;
+use crate::api::submit_listens::SubmittedTrackMetadata;
+use crate::api::user::username::fresh_releases::FreshReleaseResponse;
+use crate::api::user::username::fresh_releases::FreshReleaseSort;
+use crate::api::user::username::listen_count::UserListenCountResponse;
+use crate::api::user::username::listens::UserListensResponse;
+use crate::api::user::username::playing_now::UserPlayingNowResponse;
+use crate::api::validate_token::ValidateTokenResponse;
+use crate::client::ListenBrainzClient;
+use crate::error::Error;
+use crate::models::mbid::RecordingMbid;
+use crate::models::timestamp::Timestamp;
+use crate::models::token::AuthenticatedRequest;
+use crate::models::token::UserToken;
+use crate::parser::ListenBrainzParser;
+
+// === Sync ===
+
+#[cfg(feature = "sync")]
+#[bon::bon]
+impl ListenBrainzClient {
+    /// Send the request, and parse its response
+    pub fn fetch<T>(&self, mut request: ApiRequest<ListenBrainzParser<T>>) -> Result<T, Error>
+    where
+        T: DeserializeOwned + Sync,
+    {
+        Ok(self.send(&mut request)?.parse()?)
+    }
+
+    /// Send the request needing a token, and parse its response
+    pub fn fetch_authenticated<T>(
+        &self,
+        mut request: AuthenticatedRequest<ListenBrainzParser<T>>,
+    ) -> Result<T, Error>
+    where
+        T: DeserializeOwned + Sync,
+    {
+        Ok(self.send_authenticated(&mut request)?.parse()?)
+    }
+
+    /// Fetch the listens of the user. See [`crate::ListenBrainzAPIEnpoints::get_user_username_listens`]
+    #[builder]
+    pub fn get_user_username_listens(
+        &self,
+        username: &str,
+        #[builder(into)] max_ts: Option<Timestamp>,
+        #[builder(into)] min_ts: Option<Timestamp>,
+        count: Option<u64>,
+    ) -> Result<UserListensResponse, Error> {
+        self.fetch(
+            self.endpoints()
+                .get_user_username_listens()
+                .username(username)
+                .maybe_max_ts(max_ts)
+                .maybe_min_ts(min_ts)
+                .maybe_count(count)
+                .call()?,
+        )
+    }
+
+    /// Fetch the total number of listens of the user
+    pub fn get_user_username_listen_count(
+        &self,
+        username: &str,
+    ) -> Result<UserListenCountResponse, Error> {
+        self.fetch(self.endpoints().get_user_username_listen_count(username)?)
+    }
+
+    /// Fetch the track the user is currently listening to
+    pub fn get_user_username_playing_now(
+        &self,
+        username: &str,
+    ) -> Result<UserPlayingNowResponse, Error> {
+        self.fetch(self.endpoints().get_user_username_playing_now(username)?)
+    }
+
+    /// Fetch the fresh releases of the user. See [`crate::ListenBrainzAPIEnpoints::get_user_username_fresh_releases`]
+    #[builder]
+    pub fn get_user_username_fresh_releases(
+        &self,
+        username: &str,
+        sort: Option<FreshReleaseSort>,
+        past: Option<bool>,
+        future: Option<bool>,
+        days: Option<u64>,
+    ) -> Result<FreshReleaseResponse, Error> {
+        self.fetch(
+            self.endpoints()
+                .get_user_username_fresh_releases()
+                .username(username)
+                .maybe_sort(sort)
+                .maybe_past(past)
+                .maybe_future(future)
+                .maybe_days(days)
+                .call()?,
+        )
+    }
+
+    /// Fetch the popularity of recordings
+    pub fn post_popularity_recording(
+        &self,
+        recording_mbids: Vec<RecordingMbid>,
+    ) -> Result<Vec<PopularityRecordingResponse>, Error> {
+        self.fetch(
+            self.endpoints()
+                .post_popularity_recording(recording_mbids)?,
+        )
+    }
+
+    /// Submit listens, or update the "playing now" status of the user
+    pub fn post_submit_listens(
+        &self,
+        payload: SubmitListensPayload,
+        token: UserToken,
+    ) -> Result<SubmitListensResponse, Error> {
+        self.fetch_authenticated(self.endpoints().post_submit_listens(payload, token)?)
+    }
+
+    /// Set the track the user owning the token is currently listening to
+    pub fn post_submit_playing_now(
+        &self,
+        track_metadata: SubmittedTrackMetadata,
+        token: UserToken,
+    ) -> Result<SubmitListensResponse, Error> {
+        self.fetch_authenticated(
+            self.endpoints()
+                .post_submit_playing_now(track_metadata, token)?,
+        )
+    }
+
+    /// Delete a listen of the user owning the token
+    pub fn post_delete_listen(
+        &self,
+        listen: DeleteListenBody,
+        token: UserToken,
+    ) -> Result<DeleteListenResponse, Error> {
+        self.fetch_authenticated(self.endpoints().post_delete_listen(listen, token)?)
+    }
+
+    /// Link a listen to a MusicBrainz recording
+    pub fn post_submit_manual_mapping(
+        &self,
+        mapping: SubmitManualMappingBody,
+        token: UserToken,
+    ) -> Result<Vec<SubmitManualMappingBody>, Error> {
+        self.fetch_authenticated(
+            self.endpoints()
+                .post_submit_manual_mapping(mapping, token)?,
+        )
+    }
+
+    /// Check the validity of a token. An invalid token isn't an error, but a response with `valid` set to `false`
+    pub fn post_validate_token(&self, token: UserToken) -> Result<ValidateTokenResponse, Error> {
+        self.fetch_authenticated(self.endpoints().post_validate_token(token)?)
+    }
+}
+
+// === Async ===
+
+#[cfg(feature = "async")]
+#[bon::bon]
+impl ListenBrainzClient {
+    /// Send the request, and parse its response
+    pub async fn fetch_async<T>(
+        &self,
+        mut request: ApiRequest<ListenBrainzParser<T>>,
+    ) -> Result<T, Error>
+    where
+        T: DeserializeOwned + Sync,
+    {
+        Ok(self.send_async(&mut request).await?.parse()?)
+    }
+
+    /// Send the request needing a token, and parse its response
+    pub async fn fetch_authenticated_async<T>(
+        &self,
+        mut request: AuthenticatedRequest<ListenBrainzParser<T>>,
+    ) -> Result<T, Error>
+    where
+        T: DeserializeOwned + Sync,
+    {
+        Ok(self.send_authenticated_async(&mut request).await?.parse()?)
+    }
+
+    /// Fetch the listens of the user. See [`crate::ListenBrainzAPIEnpoints::get_user_username_listens`]
+    #[builder]
+    pub async fn get_user_username_listens_async(
+        &self,
+        username: &str,
+        #[builder(into)] max_ts: Option<Timestamp>,
+        #[builder(into)] min_ts: Option<Timestamp>,
+        count: Option<u64>,
+    ) -> Result<UserListensResponse, Error> {
+        self.fetch_async(
+            self.endpoints()
+                .get_user_username_listens()
+                .username(username)
+                .maybe_max_ts(max_ts)
+                .maybe_min_ts(min_ts)
+                .maybe_count(count)
+                .call()?,
+        )
+        .await
+    }
+
+    /// Fetch the total number of listens of the user
+    pub async fn get_user_username_listen_count_async(
+        &self,
+        username: &str,
+    ) -> Result<UserListenCountResponse, Error> {
+        self.fetch_async(self.endpoints().get_user_username_listen_count(username)?)
+            .await
+    }
+
+    /// Fetch the track the user is currently listening to
+    pub async fn get_user_username_playing_now_async(
+        &self,
+        username: &str,
+    ) -> Result<UserPlayingNowResponse, Error> {
+        self.fetch_async(self.endpoints().get_user_username_playing_now(username)?)
+            .await
+    }
+
+    /// Fetch the fresh releases of the user. See [`crate::ListenBrainzAPIEnpoints::get_user_username_fresh_releases`]
+    #[builder]
+    pub async fn get_user_username_fresh_releases_async(
+        &self,
+        username: &str,
+        sort: Option<FreshReleaseSort>,
+        past: Option<bool>,
+        future: Option<bool>,
+        days: Option<u64>,
+    ) -> Result<FreshReleaseResponse, Error> {
+        self.fetch_async(
+            self.endpoints()
+                .get_user_username_fresh_releases()
+                .username(username)
+                .maybe_sort(sort)
+                .maybe_past(past)
+                .maybe_future(future)
+                .maybe_days(days)
+                .call()?,
+        )
+        .await
+    }
+
+    /// Fetch the popularity of recordings
+    pub async fn post_popularity_recording_async(
+        &self,
+        recording_mbids: Vec<RecordingMbid>,
+    ) -> Result<Vec<PopularityRecordingResponse>, Error> {
+        self.fetch_async(
+            self.endpoints()
+                .post_popularity_recording(recording_mbids)?,
+        )
+        .await
+    }
+
+    /// Submit listens, or update the "playing now" status of the user
+    pub async fn post_submit_listens_async(
+        &self,
+        payload: SubmitListensPayload,
+        token: UserToken,
+    ) -> Result<SubmitListensResponse, Error> {
+        self.fetch_authenticated_async(self.endpoints().post_submit_listens(payload, token)?)
+            .await
+    }
+
+    /// Set the track the user owning the token is currently listening to
+    pub async fn post_submit_playing_now_async(
+        &self,
+        track_metadata: SubmittedTrackMetadata,
+        token: UserToken,
+    ) -> Result<SubmitListensResponse, Error> {
+        self.fetch_authenticated_async(
+            self.endpoints()
+                .post_submit_playing_now(track_metadata, token)?,
+        )
+        .await
+    }
+
+    /// Delete a listen of the user owning the token
+    pub async fn post_delete_listen_async(
+        &self,
+        listen: DeleteListenBody,
+        token: UserToken,
+    ) -> Result<DeleteListenResponse, Error> {
+        self.fetch_authenticated_async(self.endpoints().post_delete_listen(listen, token)?)
+            .await
+    }
+
+    /// Link a listen to a MusicBrainz recording
+    pub async fn post_submit_manual_mapping_async(
+        &self,
+        mapping: SubmitManualMappingBody,
+        token: UserToken,
+    ) -> Result<Vec<SubmitManualMappingBody>, Error> {
+        self.fetch_authenticated_async(
+            self.endpoints()
+                .post_submit_manual_mapping(mapping, token)?,
+        )
+        .await
+    }
+
+    /// Check the validity of a token. An invalid token isn't an error, but a response with `valid` set to `false`
+    pub async fn post_validate_token_async(
+        &self,
+        token: UserToken,
+    ) -> Result<ValidateTokenResponse, Error> {
+        self.fetch_authenticated_async(self.endpoints().post_validate_token(token)?)
+            .await
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "async")]
+mod test {
+    use macro_rules_attribute::apply;
+
+    use crate::client::cassette::fixture_cassette_client;
+    use crate::mock_server::MockListenBrainzServer;
+
+    #[apply(smol_macros::test!)]
+    async fn get_user_username_listen_count_async_test() {
+        let server = MockListenBrainzServer::start_with_fixtures().unwrap();
+        let client = server.client();
+
+        let res = client
+            .get_user_username_listen_count_async("RustyNova")
+            .await
+            .unwrap();
+
+        assert!(res.payload.count > 0);
+    }
+
+    #[apply(smol_macros::test!)]
+    async fn get_user_username_listen_count_cassette_test() {
+        let client = fixture_cassette_client("listen_count");
+
+        let res = client
+            .get_user_username_listen_count_async("RustyNova")
+            .await
+            .unwrap();
+
+        assert!(res.payload.count > 0);
+    }
+}
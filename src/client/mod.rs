@@ -1,11 +1,50 @@
 #[cfg(feature = "async")]
 use std::sync::Arc;
+#[cfg(any(feature = "sync", feature = "async"))]
+use std::time::Instant;
 
 use api_bindium::ApiClient;
+#[cfg(any(feature = "sync", feature = "async"))]
+use api_bindium::ApiRequest;
+#[cfg(any(feature = "sync", feature = "async"))]
+use api_bindium::ApiRequestError;
+#[cfg(any(feature = "sync", feature = "async"))]
+use api_bindium::api_response::ureq_response::UreqResponse;
 #[cfg(feature = "async")]
 use async_executor::Executor;
 
+#[cfg(any(feature = "sync", feature = "async"))]
+use serde::de::DeserializeOwned;
+
 use crate::api::ListenBrainzAPIEnpoints;
+#[cfg(any(feature = "sync", feature = "async"))]
+use crate::client::cache::CacheLookup;
+#[cfg(any(feature = "sync", feature = "async"))]
+use crate::client::cache::PendingEntry;
+#[cfg(any(feature = "sync", feature = "async"))]
+use crate::client::cache::ResponseCache;
+#[cfg(any(feature = "sync", feature = "async"))]
+use crate::client::cassette::Cassette;
+use crate::client::rate_limit::RateLimitBudget;
+use crate::client::rate_limit::RateLimitTracker;
+#[cfg(any(feature = "sync", feature = "async"))]
+use crate::client::retry::RetryPolicy;
+#[cfg(any(feature = "sync", feature = "async"))]
+use crate::models::token::AuthenticatedRequest;
+#[cfg(any(feature = "sync", feature = "async"))]
+use crate::parser::ListenBrainzParser;
+
+#[cfg(any(feature = "sync", feature = "async"))]
+pub mod cache;
+#[cfg(any(feature = "sync", feature = "async"))]
+pub mod cassette;
+#[cfg(any(feature = "sync", feature = "async"))]
+pub mod fetch;
+pub mod rate_limit;
+#[cfg(any(feature = "sync", feature = "async"))]
+pub mod retry;
+#[cfg(any(feature = "sync", feature = "async"))]
+mod stored_response;
 
 #[derive(Debug, bon::Builder, Clone)]
 pub struct ListenBrainzClient {
@@ -19,6 +58,28 @@ pub struct ListenBrainzClient {
 
     #[builder(default)]
     endpoints: ListenBrainzAPIEnpoints,
+
+    /// The latest rate limit budget returned by the server
+    #[builder(skip)]
+    rate_limit: RateLimitTracker,
+
+    /// Which failed requests are sent again
+    #[cfg(any(feature = "sync", feature = "async"))]
+    #[builder(default)]
+    retry_policy: RetryPolicy,
+
+    /// Record the responses to disk, or replay them instead of sending the requests
+    #[cfg(any(feature = "sync", feature = "async"))]
+    cassette: Option<Cassette>,
+
+    /// Reuse the responses of the requests that don't need a token
+    #[cfg(any(feature = "sync", feature = "async"))]
+    cache: Option<ResponseCache>,
+
+    /// Ignore the cached responses, and always ask the server. The new responses are still cached
+    #[cfg(any(feature = "sync", feature = "async"))]
+    #[builder(default)]
+    bypass_cache: bool,
 }
 
 impl ListenBrainzClient {
@@ -30,10 +91,324 @@ impl ListenBrainzClient {
         &self.api_client
     }
 
+    #[cfg(any(feature = "sync", feature = "async"))]
+    pub fn cassette(&self) -> Option<&Cassette> {
+        self.cassette.as_ref()
+    }
+
+    #[cfg(any(feature = "sync", feature = "async"))]
+    pub fn retry_policy(&self) -> &RetryPolicy {
+        &self.retry_policy
+    }
+
+    #[cfg(any(feature = "sync", feature = "async"))]
+    pub fn cache(&self) -> Option<&ResponseCache> {
+        self.cache.as_ref()
+    }
+
+    /// Return a copy of the client that ignores the cached responses, and always asks the server.
+    ///
+    /// The copy shares its cache, rate limit and cassette with this client
+    #[cfg(any(feature = "sync", feature = "async"))]
+    #[must_use]
+    pub fn bypassing_cache(&self) -> Self {
+        Self {
+            bypass_cache: true,
+            ..self.clone()
+        }
+    }
+
+    /// The current request budget, as given by the `X-RateLimit-*` headers of the latest response.
+    ///
+    /// Return `None` if no response got received yet
+    pub fn rate_limit_budget(&self) -> Option<RateLimitBudget> {
+        self.rate_limit.budget()
+    }
+
+    /// Send the request, pausing until the rate limit resets if the budget is exhausted or the server responds with a 429.
+    ///
+    /// All the retries, including those pauses, are configured by the [`RetryPolicy`] of the client.
+    ///
+    /// If the client has a [`Cassette`], the response is either replayed from it, or recorded into it.
+    /// If it has a [`ResponseCache`], a fresh cached response is returned without sending the request
+    #[cfg(feature = "sync")]
+    pub fn send<T>(
+        &self,
+        request: &mut ApiRequest<ListenBrainzParser<T>>,
+    ) -> Result<UreqResponse<ListenBrainzParser<T>>, ApiRequestError>
+    where
+        T: DeserializeOwned + Sync,
+    {
+        if let Some(cassette) = self.replaying_cassette() {
+            return cassette.replay(request);
+        }
+
+        let pending = match self.cache_lookup(request)? {
+            // Still recorded, so the cassette can be replayed without the cache
+            CacheLookup::Hit(response) => return self.record(request, response),
+            CacheLookup::Miss(pending) => Some(pending),
+            CacheLookup::Uncached => None,
+        };
+
+        let api_client = self.single_attempt_client();
+        let mut pauses = 0;
+        let mut attempt = 1;
+
+        loop {
+            if let Some(wait) = self.rate_limit.wait_time() {
+                std::thread::sleep(wait);
+            }
+
+            reset_attempt(request);
+            let response = match request.send(&api_client) {
+                Ok(response) => response,
+                Err(err) => {
+                    // The other requests of the client must also wait if the server asked to (ex: the `Retry-After` of a 503)
+                    self.rate_limit
+                        .pause_for(self.retry_policy.requested_delay(request));
+
+                    match self.retry_policy.delay_after_error(request, &err, attempt) {
+                        Some(delay) => {
+                            std::thread::sleep(delay);
+                            attempt += 1;
+                            continue;
+                        }
+                        None => {
+                            if let Some(pending) = pending {
+                                pending.discard(request);
+                            }
+                            return Err(err);
+                        }
+                    }
+                }
+            };
+            self.rate_limit.update(&response.inner.data);
+
+            // Rate limited requests are paused instead, and don't count as failed attempts
+            if response.inner.data.status().as_u16() == 429
+                && pauses < self.retry_policy.max_rate_limit_pauses()
+            {
+                pauses += 1;
+                continue;
+            }
+
+            match self
+                .retry_policy
+                .delay_after_response(request, &response.inner.data, attempt)
+            {
+                Some(delay) => {
+                    std::thread::sleep(delay);
+                    attempt += 1;
+                }
+                None => return self.finish(request, response, pending),
+            }
+        }
+    }
+
+    /// Send the request, pausing until the rate limit resets if the budget is exhausted or the server responds with a 429.
+    ///
+    /// All the retries, including those pauses, are configured by the [`RetryPolicy`] of the client.
+    ///
+    /// If the client has a [`Cassette`], the response is either replayed from it, or recorded into it.
+    /// If it has a [`ResponseCache`], a fresh cached response is returned without sending the request
+    #[cfg(feature = "async")]
+    pub async fn send_async<T>(
+        &self,
+        request: &mut ApiRequest<ListenBrainzParser<T>>,
+    ) -> Result<UreqResponse<ListenBrainzParser<T>>, ApiRequestError>
+    where
+        T: DeserializeOwned + Sync,
+    {
+        if let Some(cassette) = self.replaying_cassette() {
+            return cassette.replay(request);
+        }
+
+        let pending = match self.cache_lookup(request)? {
+            // Still recorded, so the cassette can be replayed without the cache
+            CacheLookup::Hit(response) => return self.record(request, response),
+            CacheLookup::Miss(pending) => Some(pending),
+            CacheLookup::Uncached => None,
+        };
+
+        let api_client = self.single_attempt_client();
+        let mut pauses = 0;
+        let mut attempt = 1;
+
+        loop {
+            if let Some(wait) = self.rate_limit.wait_time() {
+                async_io::Timer::after(wait).await;
+            }
+
+            reset_attempt(request);
+            let response = match request.send_async(&api_client).await {
+                Ok(response) => response,
+                Err(err) => {
+                    // The other requests of the client must also wait if the server asked to (ex: the `Retry-After` of a 503)
+                    self.rate_limit
+                        .pause_for(self.retry_policy.requested_delay(request));
+
+                    match self.retry_policy.delay_after_error(request, &err, attempt) {
+                        Some(delay) => {
+                            async_io::Timer::after(delay).await;
+                            attempt += 1;
+                            continue;
+                        }
+                        None => {
+                            if let Some(pending) = pending {
+                                pending.discard(request);
+                            }
+                            return Err(err);
+                        }
+                    }
+                }
+            };
+            self.rate_limit.update(&response.inner.data);
+
+            // Rate limited requests are paused instead, and don't count as failed attempts
+            if response.inner.data.status().as_u16() == 429
+                && pauses < self.retry_policy.max_rate_limit_pauses()
+            {
+                pauses += 1;
+                continue;
+            }
+
+            match self
+                .retry_policy
+                .delay_after_response(request, &response.inner.data, attempt)
+            {
+                Some(delay) => {
+                    async_io::Timer::after(delay).await;
+                    attempt += 1;
+                }
+                None => return self.finish(request, response, pending),
+            }
+        }
+    }
+
+    /// Send a request needing a token. The token is only added to the headers of the request while it is sent.
+    ///
+    /// See [`Self::send`]
+    #[cfg(feature = "sync")]
+    pub fn send_authenticated<T>(
+        &self,
+        request: &mut AuthenticatedRequest<ListenBrainzParser<T>>,
+    ) -> Result<UreqResponse<ListenBrainzParser<T>>, ApiRequestError>
+    where
+        T: DeserializeOwned + Sync,
+    {
+        self.send(&mut request.authorize())
+    }
+
+    /// Send a request needing a token. The token is only added to the headers of the request while it is sent.
+    ///
+    /// See [`Self::send_async`]
+    #[cfg(feature = "async")]
+    pub async fn send_authenticated_async<T>(
+        &self,
+        request: &mut AuthenticatedRequest<ListenBrainzParser<T>>,
+    ) -> Result<UreqResponse<ListenBrainzParser<T>>, ApiRequestError>
+    where
+        T: DeserializeOwned + Sync,
+    {
+        self.send_async(&mut request.authorize()).await
+    }
+
+    /// A copy of the api client that sends each request only once, as the retries are handled by the [`RetryPolicy`]
+    #[cfg(any(feature = "sync", feature = "async"))]
+    fn single_attempt_client(&self) -> ApiClient {
+        // `api_bindium` stops once the number of attempts reaches `max_retries`, so 1 sends the request once
+        ApiClient {
+            max_retries: 1,
+            ..self.api_client.clone()
+        }
+    }
+
+    /// Return the cassette if the responses must be replayed from it
+    #[cfg(any(feature = "sync", feature = "async"))]
+    fn replaying_cassette(&self) -> Option<&Cassette> {
+        self.cassette
+            .as_ref()
+            .filter(|cassette| !cassette.is_recording())
+    }
+
+    /// Look for the response in the cache, if there's one
+    #[cfg(any(feature = "sync", feature = "async"))]
+    fn cache_lookup<T>(
+        &self,
+        request: &mut ApiRequest<ListenBrainzParser<T>>,
+    ) -> Result<CacheLookup<T>, ApiRequestError>
+    where
+        T: DeserializeOwned,
+    {
+        match &self.cache {
+            Some(cache) => cache.lookup(request, self.bypass_cache),
+            None => Ok(CacheLookup::Uncached),
+        }
+    }
+
+    /// Store the response of the server in the cache and the cassette, if there are some
+    #[cfg(any(feature = "sync", feature = "async"))]
+    fn finish<T>(
+        &self,
+        request: &mut ApiRequest<ListenBrainzParser<T>>,
+        response: UreqResponse<ListenBrainzParser<T>>,
+        pending: Option<PendingEntry>,
+    ) -> Result<UreqResponse<ListenBrainzParser<T>>, ApiRequestError>
+    where
+        T: DeserializeOwned,
+    {
+        // The cache goes first, so a `304 Not Modified` is recorded as the response it stands for
+        let response = match (&self.cache, pending) {
+            (Some(cache), Some(pending)) => cache.store(request, response, pending)?,
+            _ => response,
+        };
+
+        self.record(request, response)
+    }
+
+    /// Record the response in the cassette, if there's one
+    #[cfg(any(feature = "sync", feature = "async"))]
+    fn record<T>(
+        &self,
+        request: &ApiRequest<ListenBrainzParser<T>>,
+        response: UreqResponse<ListenBrainzParser<T>>,
+    ) -> Result<UreqResponse<ListenBrainzParser<T>>, ApiRequestError>
+    where
+        T: DeserializeOwned,
+    {
+        match &self.cassette {
+            Some(cassette) => cassette.record(request, response),
+            None => Ok(response),
+        }
+    }
+
     #[cfg(feature = "async")]
     pub fn async_executor(&self) -> &Arc<Executor<'static>> {
         &self.async_executor
     }
+
+    /// Run the futures concurrently on the [`Self::async_executor`], and return their outputs in the same order
+    #[cfg(feature = "async")]
+    pub(crate) async fn run_concurrently<F, T>(&self, futures: Vec<F>) -> Vec<T>
+    where
+        F: Future<Output = T> + Send + 'static,
+        T: Send + 'static,
+    {
+        let tasks = futures
+            .into_iter()
+            .map(|future| self.async_executor.spawn(future))
+            .collect::<Vec<_>>();
+
+        self.async_executor
+            .run(async {
+                let mut results = Vec::with_capacity(tasks.len());
+                for task in tasks {
+                    results.push(task.await);
+                }
+                results
+            })
+            .await
+    }
 }
 
 impl Default for ListenBrainzClient {
@@ -41,3 +416,10 @@ impl Default for ListenBrainzClient {
         Self::builder().build()
     }
 }
+
+/// Clear the retry state that `api_bindium` keeps in the request, so the next attempt is sent right away
+#[cfg(any(feature = "sync", feature = "async"))]
+fn reset_attempt<P>(request: &mut ApiRequest<P>) {
+    request.reset();
+    request.retry_after = Instant::now();
+}
@@ -0,0 +1,215 @@
+use core::time::Duration;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Instant;
+
+#[cfg(any(feature = "sync", feature = "async"))]
+use api_bindium::ureq::Body;
+use api_bindium::ureq::http::HeaderMap;
+#[cfg(any(feature = "sync", feature = "async"))]
+use api_bindium::ureq::http::Response;
+
+/// How long to wait after a 429 response that doesn't tell when the limit resets
+#[cfg(any(feature = "sync", feature = "async"))]
+const DEFAULT_RATE_LIMIT_PAUSE: Duration = Duration::from_secs(1);
+
+/// The request budget of the client, as given by the `X-RateLimit-*` headers of ListenBrainz
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimitBudget {
+    /// The number of requests allowed in the current window
+    pub limit: u64,
+
+    /// The number of requests left in the current window
+    pub remaining: u64,
+
+    /// When the current window ends, and the budget gets refilled
+    pub reset_at: Instant,
+}
+
+impl RateLimitBudget {
+    /// Read the budget from the headers of a response. Return `None` if the headers are missing
+    pub fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        let header =
+            |name: &str| -> Option<u64> { headers.get(name)?.to_str().ok()?.trim().parse().ok() };
+
+        Some(Self {
+            limit: header("X-RateLimit-Limit")?,
+            remaining: header("X-RateLimit-Remaining")?,
+            reset_at: Instant::now() + Duration::from_secs(header("X-RateLimit-Reset-In")?),
+        })
+    }
+
+    /// Return true if no requests can be sent until the window resets
+    pub fn is_exhausted(&self) -> bool {
+        self.remaining == 0 && self.reset_at > Instant::now()
+    }
+}
+
+/// Keep track of the latest budget returned by the server. This is shared between the clones of a client
+#[derive(Debug, Clone, Default)]
+pub(crate) struct RateLimitTracker(Arc<Mutex<Option<RateLimitBudget>>>);
+
+impl RateLimitTracker {
+    pub fn budget(&self) -> Option<RateLimitBudget> {
+        *self.0.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    /// Update the budget with the headers of a response
+    #[cfg(any(feature = "sync", feature = "async"))]
+    pub fn update(&self, response: &Response<Body>) {
+        let budget = RateLimitBudget::from_headers(response.headers());
+        let mut current = self.0.lock().unwrap_or_else(|err| err.into_inner());
+
+        let rate_limited = response.status().as_u16() == 429;
+
+        match budget {
+            // The request got refused, so nothing is left whatever the headers say
+            Some(budget) if rate_limited => {
+                *current = Some(RateLimitBudget {
+                    remaining: 0,
+                    ..budget
+                });
+            }
+            Some(budget) => *current = Some(budget),
+            // Rate limited without being told for how long. Pause a bit
+            None if rate_limited => {
+                *current = Some(RateLimitBudget {
+                    limit: current.map(|budget| budget.limit).unwrap_or_default(),
+                    remaining: 0,
+                    reset_at: Instant::now() + DEFAULT_RATE_LIMIT_PAUSE,
+                });
+            }
+            None => {}
+        }
+    }
+
+    /// Hold the next requests back for `delay`, as if the budget was exhausted.
+    ///
+    /// This is used when the server asks to retry later without sending its budget, like in a 503 response
+    #[cfg(any(feature = "sync", feature = "async"))]
+    pub fn pause_for(&self, delay: Duration) {
+        let reset_at = Instant::now() + delay;
+        let mut current = self.0.lock().unwrap_or_else(|err| err.into_inner());
+
+        // Don't shorten a longer pause
+        if delay.is_zero()
+            || current.is_some_and(|budget| budget.is_exhausted() && budget.reset_at >= reset_at)
+        {
+            return;
+        }
+
+        *current = Some(RateLimitBudget {
+            limit: current.map(|budget| budget.limit).unwrap_or_default(),
+            remaining: 0,
+            reset_at,
+        });
+    }
+
+    /// How long to wait before sending the next request
+    #[cfg(any(feature = "sync", feature = "async"))]
+    pub fn wait_time(&self) -> Option<Duration> {
+        self.budget()
+            .filter(RateLimitBudget::is_exhausted)
+            .map(|budget| budget.reset_at.saturating_duration_since(Instant::now()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #[cfg(any(feature = "sync", feature = "async"))]
+    use std::time::Instant;
+
+    use api_bindium::ureq::http::HeaderMap;
+    use api_bindium::ureq::http::HeaderValue;
+    #[cfg(feature = "async")]
+    use macro_rules_attribute::apply;
+
+    use crate::client::rate_limit::RateLimitBudget;
+    #[cfg(any(feature = "sync", feature = "async"))]
+    use crate::mock_server::MockListenBrainzServer;
+    #[cfg(any(feature = "sync", feature = "async"))]
+    use crate::mock_server::data::FIXTURE_USER;
+
+    #[test]
+    fn budget_from_headers_test() {
+        let mut headers = HeaderMap::new();
+        headers.insert("X-RateLimit-Limit", HeaderValue::from_static("30"));
+        headers.insert("X-RateLimit-Remaining", HeaderValue::from_static("0"));
+        headers.insert("X-RateLimit-Reset-In", HeaderValue::from_static("5"));
+
+        let budget = RateLimitBudget::from_headers(&headers).unwrap();
+        assert_eq!(budget.limit, 30);
+        assert_eq!(budget.remaining, 0);
+        assert!(budget.is_exhausted());
+
+        headers.remove("X-RateLimit-Reset-In");
+        assert_eq!(RateLimitBudget::from_headers(&headers), None);
+    }
+
+    /// A mock server rate limiting the next two requests for a second.
+    ///
+    /// Its 429 responses still claim that requests are remaining, so the client must not trust them
+    #[cfg(any(feature = "sync", feature = "async"))]
+    fn rate_limited_server() -> MockListenBrainzServer {
+        let server = MockListenBrainzServer::start_with_fixtures().unwrap();
+        server.data().rate_limit_reset_in = Some(1);
+        server.data().queued_errors.extend([429, 429]);
+        server
+    }
+
+    #[cfg(feature = "sync")]
+    #[test]
+    fn rate_limit_pause_test() {
+        let server = rate_limited_server();
+        let client = server.client();
+
+        let start = Instant::now();
+        let res = client.get_user_username_listen_count(FIXTURE_USER).unwrap();
+
+        assert!(res.payload.count > 0);
+        assert_eq!(server.request_count(), 3);
+        assert!(start.elapsed().as_secs_f64() >= 1.5);
+    }
+
+    #[cfg(feature = "async")]
+    #[apply(smol_macros::test!)]
+    async fn rate_limit_pause_async_test() {
+        let server = rate_limited_server();
+        let client = server.client();
+
+        let start = Instant::now();
+        let res = client
+            .get_user_username_listen_count_async(FIXTURE_USER)
+            .await
+            .unwrap();
+
+        assert!(res.payload.count > 0);
+        assert_eq!(server.request_count(), 3);
+        assert!(start.elapsed().as_secs_f64() >= 1.5);
+    }
+
+    #[cfg(feature = "async")]
+    #[apply(smol_macros::test!)]
+    async fn retry_after_pause_test() {
+        use crate::client::ListenBrainzClient;
+        use crate::client::retry::RetryPolicy;
+
+        let server = MockListenBrainzServer::start_with_fixtures().unwrap();
+        server.data().queued_errors.push_back(503);
+        server.data().retry_after = Some(2);
+
+        let client = ListenBrainzClient::builder()
+            .endpoints(server.endpoints())
+            .retry_policy(RetryPolicy::none())
+            .build();
+        client
+            .get_user_username_listen_count_async(FIXTURE_USER)
+            .await
+            .unwrap_err();
+
+        // The next requests of the client wait for the server too
+        let budget = client.rate_limit_budget().unwrap();
+        assert!(budget.is_exhausted());
+        assert!(budget.reset_at >= Instant::now() + core::time::Duration::from_secs(1));
+    }
+}
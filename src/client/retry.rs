@@ -0,0 +1,393 @@
+use core::hash::BuildHasher as _;
+use core::hash::Hasher as _;
+use core::time::Duration;
+use std::hash::RandomState;
+use std::time::Instant;
+
+use api_bindium::ApiRequest;
+use api_bindium::ApiRequestError;
+use api_bindium::HTTPVerb;
+use api_bindium::ureq;
+use api_bindium::ureq::Body;
+use api_bindium::ureq::http::Response;
+
+use crate::client::cache::endpoint_matches;
+
+/// The statuses retried by default. Those are temporary errors of the server or its proxy.
+///
+/// `503 Service Unavailable` isn't listed, as `api_bindium` reports it as an error. See `retry_temporary_errors`
+pub const DEFAULT_RETRYABLE_STATUSES: [u16; 3] = [500, 502, 504];
+
+/// The POST endpoints that can safely be sent multiple times. They either only read data, or set it to a fixed value
+const IDEMPOTENT_POST_ENDPOINTS: [&str; 3] = [
+    "/1/popularity/recording",
+    "/1/delete-listen",
+    "/1/metadata/submit_manual_mapping",
+];
+
+/// When to send a request again after a temporary failure, and how long to wait between the attempts.
+///
+/// The pauses grow exponentially from [`RetryPolicy::builder`]'s `initial_backoff`, and are randomized to spread concurrent retries.
+/// If the server sends a `Retry-After` header, the pause lasts at least that long, up to `max_backoff`.
+///
+/// Requests that aren't idempotent, like listen submissions, are only retried when they couldn't reach the server,
+/// so a listen doesn't get submitted twice.
+///
+/// Rate limited requests (429) are sent again once the rate limit resets, up to `max_rate_limit_pauses` times.
+/// Those pauses don't count as attempts.
+#[derive(Debug, Clone, bon::Builder)]
+pub struct RetryPolicy {
+    /// The maximum number of attempts of a request, including the first one. `1` disables the retries
+    #[builder(default = 4)]
+    max_attempts: u32,
+
+    /// How many times a rate limited request waits for the rate limit to reset before giving up.
+    /// The last 429 response is then returned
+    #[builder(default = 10)]
+    max_rate_limit_pauses: u32,
+
+    /// The pause before the first retry. It doubles after each retry
+    #[builder(default = Duration::from_millis(500))]
+    initial_backoff: Duration,
+
+    /// The longest pause between two attempts
+    #[builder(default = Duration::from_secs(30))]
+    max_backoff: Duration,
+
+    /// Randomize the pauses, so concurrent requests don't retry all at once
+    #[builder(default = true)]
+    jitter: bool,
+
+    /// The statuses of the responses to retry
+    #[builder(default = DEFAULT_RETRYABLE_STATUSES.to_vec())]
+    retryable_statuses: Vec<u16>,
+
+    /// Retry the requests that failed without a usable response: refused connections, timeouts, connection resets,
+    /// and `503 Service Unavailable` responses
+    #[builder(default = true)]
+    retry_temporary_errors: bool,
+
+    /// Also retry the requests that aren't idempotent after they may have reached the server.
+    /// This may create duplicates, like listens submitted twice
+    #[builder(default)]
+    retry_non_idempotent: bool,
+}
+
+impl RetryPolicy {
+    /// A policy that never retries, nor waits for the rate limit to reset
+    pub fn none() -> Self {
+        Self::builder()
+            .max_attempts(1)
+            .max_rate_limit_pauses(0)
+            .build()
+    }
+
+    pub fn max_rate_limit_pauses(&self) -> u32 {
+        self.max_rate_limit_pauses
+    }
+
+    /// Return true if sending the request multiple times has the same effect as sending it once
+    pub fn is_idempotent<P>(request: &ApiRequest<P>) -> bool {
+        match request.verb() {
+            HTTPVerb::Get => true,
+            HTTPVerb::Post => IDEMPOTENT_POST_ENDPOINTS
+                .iter()
+                .any(|endpoint| endpoint_matches(endpoint, request.uri().path())),
+        }
+    }
+
+    /// The pause before the `retry`th retry (starting at 1), without jitter
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let factor = 2_u32.saturating_pow(retry.saturating_sub(1));
+
+        self.initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff)
+    }
+
+    /// Return how long to wait before retrying a request that got this response, or `None` if it mustn't be retried.
+    ///
+    /// `attempt` is the number of attempts made so far
+    pub(crate) fn delay_after_response<P>(
+        &self,
+        request: &ApiRequest<P>,
+        response: &Response<Body>,
+        attempt: u32,
+    ) -> Option<Duration> {
+        let retryable = self
+            .retryable_statuses
+            .contains(&response.status().as_u16());
+
+        if !retryable || !self.may_resend(request, attempt) {
+            return None;
+        }
+
+        let backoff = self.jittered_backoff(attempt);
+        Some(
+            retry_after(response).map_or(backoff, |delay| delay.min(self.max_backoff).max(backoff)),
+        )
+    }
+
+    /// Return how long to wait before retrying a request that failed with this error, or `None` if it mustn't be retried
+    ///
+    /// `attempt` is the number of attempts made so far
+    pub(crate) fn delay_after_error<P>(
+        &self,
+        request: &ApiRequest<P>,
+        error: &ApiRequestError,
+        attempt: u32,
+    ) -> Option<Duration> {
+        if !self.retry_temporary_errors || attempt >= self.max_attempts {
+            return None;
+        }
+
+        // The server never saw the request, so even submissions can be sent again
+        let resendable = if is_connection_error(error) {
+            true
+        } else {
+            is_temporary_error(error) && self.may_resend(request, attempt)
+        };
+
+        resendable.then(|| {
+            self.jittered_backoff(attempt)
+                .max(self.requested_delay(request))
+        })
+    }
+
+    /// How long the server asked to wait before sending the request again, up to `max_backoff`.
+    ///
+    /// `api_bindium` reads the `Retry-After` header of a 503 into the `retry_after` of the request,
+    /// and falls back to its own backoff if there's none
+    pub(crate) fn requested_delay<P>(&self, request: &ApiRequest<P>) -> Duration {
+        request
+            .retry_after
+            .saturating_duration_since(Instant::now())
+            .min(self.max_backoff)
+    }
+
+    /// Return true if the request may be sent again, knowing that the server may have received it
+    fn may_resend<P>(&self, request: &ApiRequest<P>, attempt: u32) -> bool {
+        attempt < self.max_attempts && (self.retry_non_idempotent || Self::is_idempotent(request))
+    }
+
+    fn jittered_backoff(&self, retry: u32) -> Duration {
+        let backoff = self.backoff(retry);
+        if !self.jitter {
+            return backoff;
+        }
+
+        // Keep at least half the pause, so retries don't get sent right away
+        let half = backoff / 2;
+        half + half.mul_f64(random_fraction())
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::builder().build()
+    }
+}
+
+/// Read the `Retry-After` header, in seconds
+fn retry_after(response: &Response<Body>) -> Option<Duration> {
+    let seconds = response
+        .headers()
+        .get("Retry-After")?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse()
+        .ok()?;
+
+    Some(Duration::from_secs(seconds))
+}
+
+/// Return true if the error happened before the request could be sent
+fn is_connection_error(error: &ApiRequestError) -> bool {
+    let ApiRequestError::UreqError { source, .. } = error else {
+        return false;
+    };
+
+    if let ureq::Error::Io(err) = source {
+        return err.kind() == std::io::ErrorKind::ConnectionRefused;
+    }
+
+    matches!(source, ureq::Error::ConnectionFailed)
+}
+
+/// Return true if the single attempt of `api_bindium` failed with a timeout, a dropped connection or a 503.
+/// The server may have received the request
+fn is_temporary_error(error: &ApiRequestError) -> bool {
+    error.is_retryable() || matches!(error, ApiRequestError::MaxRetriesExceeded { .. })
+}
+
+/// A random number in `[0, 1)`. This doesn't need to be good randomness, so the random seeds of the std hasher are enough
+fn random_fraction() -> f64 {
+    let random = RandomState::new().build_hasher().finish();
+
+    // Keep the 53 bits that fit in the mantissa
+    (random >> 11) as f64 / (1_u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod test {
+    use core::time::Duration;
+    #[cfg(feature = "async")]
+    use std::time::Instant;
+
+    #[cfg(feature = "async")]
+    use macro_rules_attribute::apply;
+
+    use crate::api::ListenBrainzAPIEnpoints;
+    use crate::api::submit_listens::SubmitListensPayload;
+    use crate::api::submit_listens::SubmittedListen;
+    use crate::api::submit_listens::SubmittedTrackMetadata;
+    #[cfg(feature = "async")]
+    use crate::client::ListenBrainzClient;
+    use crate::client::retry::RetryPolicy;
+    #[cfg(feature = "async")]
+    use crate::mock_server::MockListenBrainzServer;
+    #[cfg(feature = "async")]
+    use crate::mock_server::data::FIXTURE_TOKEN;
+    #[cfg(feature = "async")]
+    use crate::mock_server::data::FIXTURE_USER;
+    use crate::models::token::UserToken;
+
+    fn listen() -> SubmittedListen {
+        SubmittedListen::builder()
+            .listened_at(1_800_000_000)
+            .track_metadata(
+                SubmittedTrackMetadata::builder()
+                    .artist_name("Kikuo")
+                    .track_name("Aishite")
+                    .build(),
+            )
+            .build()
+    }
+
+    #[test]
+    fn backoff_test() {
+        let policy = RetryPolicy::builder()
+            .initial_backoff(Duration::from_millis(500))
+            .max_backoff(Duration::from_secs(3))
+            .build();
+
+        assert_eq!(policy.backoff(1), Duration::from_millis(500));
+        assert_eq!(policy.backoff(2), Duration::from_secs(1));
+        assert_eq!(policy.backoff(3), Duration::from_secs(2));
+        assert_eq!(policy.backoff(4), Duration::from_secs(3));
+        assert_eq!(policy.backoff(100), Duration::from_secs(3));
+
+        for retry in 1..5 {
+            let delay = policy.jittered_backoff(retry);
+            assert!(delay >= policy.backoff(retry) / 2 && delay <= policy.backoff(retry));
+        }
+    }
+
+    #[test]
+    fn is_idempotent_test() {
+        let endpoints = ListenBrainzAPIEnpoints::default();
+
+        assert!(RetryPolicy::is_idempotent(
+            &endpoints
+                .get_user_username_listen_count("RustyNova")
+                .unwrap()
+        ));
+        assert!(RetryPolicy::is_idempotent(
+            &endpoints.post_popularity_recording(Vec::new()).unwrap()
+        ));
+        assert!(!RetryPolicy::is_idempotent(
+            endpoints
+                .post_submit_listens(
+                    SubmitListensPayload::Single(listen()),
+                    UserToken::from("token".to_string()),
+                )
+                .unwrap()
+                .request()
+        ));
+    }
+
+    #[cfg(feature = "async")]
+    fn mock_client(server: &MockListenBrainzServer) -> ListenBrainzClient {
+        ListenBrainzClient::builder()
+            .endpoints(server.endpoints())
+            .retry_policy(
+                RetryPolicy::builder()
+                    .max_attempts(3)
+                    .initial_backoff(Duration::from_millis(1))
+                    .build(),
+            )
+            .build()
+    }
+
+    #[cfg(feature = "async")]
+    #[apply(smol_macros::test!)]
+    async fn retry_temporary_error_test() {
+        let server = MockListenBrainzServer::start_with_fixtures().unwrap();
+        let client = mock_client(&server);
+        let expected = client
+            .get_user_username_listen_count_async(FIXTURE_USER)
+            .await
+            .unwrap();
+
+        // A 503, then the actual response
+        server.data().queued_errors.push_back(503);
+        let res = client
+            .get_user_username_listen_count_async(FIXTURE_USER)
+            .await
+            .unwrap();
+        assert_eq!(res.payload.count, expected.payload.count);
+        assert_eq!(server.request_count(), 3);
+
+        // Not retried after the last attempt
+        server.data().queued_errors.extend([500, 502, 500]);
+        client
+            .get_user_username_listen_count_async(FIXTURE_USER)
+            .await
+            .unwrap_err();
+        assert_eq!(server.request_count(), 6);
+    }
+
+    #[cfg(feature = "async")]
+    #[apply(smol_macros::test!)]
+    async fn retry_after_temporary_error_test() {
+        let server = MockListenBrainzServer::start_with_fixtures().unwrap();
+        let client = mock_client(&server);
+
+        server.data().queued_errors.push_back(503);
+        server.data().retry_after = Some(2);
+
+        let start = Instant::now();
+        client
+            .get_user_username_listen_count_async(FIXTURE_USER)
+            .await
+            .unwrap();
+
+        // The server asked for more than the 1ms backoff of the client
+        assert!(start.elapsed() >= Duration::from_secs(2));
+        assert_eq!(server.request_count(), 2);
+    }
+
+    #[cfg(feature = "async")]
+    #[apply(smol_macros::test!)]
+    async fn submission_not_resent_test() {
+        let server = MockListenBrainzServer::start_with_fixtures().unwrap();
+        let client = mock_client(&server);
+        let listen_count = server.data().users[FIXTURE_USER].listens.len();
+
+        server.data().queued_errors.push_back(503);
+        client
+            .post_submit_listens_async(
+                SubmitListensPayload::Single(listen()),
+                UserToken::from(FIXTURE_TOKEN.to_string()),
+            )
+            .await
+            .unwrap_err();
+
+        assert_eq!(server.request_count(), 1);
+        assert_eq!(
+            server.data().users[FIXTURE_USER].listens.len(),
+            listen_count
+        );
+    }
+}
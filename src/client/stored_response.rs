@@ -0,0 +1,97 @@
+use std::sync::Arc;
+
+use api_bindium::ApiRequest;
+use api_bindium::ApiRequestError;
+use api_bindium::api_response::ureq_response::UreqResponse;
+use api_bindium::ureq;
+use api_bindium::ureq::Body;
+use api_bindium::ureq::http::Response;
+use serde::Deserialize;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+use crate::parser::ListenBrainzParser;
+use crate::parser::ReplayedUri;
+use crate::parser::ureq_error;
+
+/// Those headers describe how the body got transfered, which doesn't apply to the decoded body we store
+const SKIPPED_HEADERS: [&str; 3] = ["content-encoding", "content-length", "transfer-encoding"];
+
+/// A response read into memory, so it can be saved and recreated later
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct StoredResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl StoredResponse {
+    /// Read the response. Its body is put back afterward, so it can still be parsed
+    pub fn read<T>(
+        request: &ApiRequest<ListenBrainzParser<T>>,
+        response: &mut UreqResponse<ListenBrainzParser<T>>,
+    ) -> Result<Self, ApiRequestError>
+    where
+        T: DeserializeOwned,
+    {
+        let data = &mut response.inner.data;
+        let body = data
+            .body_mut()
+            .with_config()
+            .limit(response.inner.max_body_size)
+            .read_to_vec()
+            .map_err(|source| ureq_error(request.uri().clone(), source))?;
+
+        let stored = Self {
+            status: data.status().as_u16(),
+            headers: data
+                .headers()
+                .iter()
+                .filter(|(name, _)| !SKIPPED_HEADERS.contains(&name.as_str()))
+                .filter_map(|(name, value)| {
+                    Some((name.to_string(), value.to_str().ok()?.to_string()))
+                })
+                .collect(),
+            body: String::from_utf8_lossy(&body).into_owned(),
+        };
+
+        *data.body_mut() = Body::builder().data(body);
+
+        Ok(stored)
+    }
+
+    /// Return the value of a header. The name is case insensitive
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Recreate the response, as if it was the response to `request`
+    pub fn to_ureq_response<T>(
+        &self,
+        request: &ApiRequest<ListenBrainzParser<T>>,
+    ) -> Result<UreqResponse<ListenBrainzParser<T>>, ApiRequestError>
+    where
+        T: DeserializeOwned,
+    {
+        let mut builder = Response::builder()
+            .status(self.status)
+            .extension(ReplayedUri(request.uri().clone()));
+
+        for (name, value) in &self.headers {
+            builder = builder.header(name, value);
+        }
+
+        let response = builder
+            .body(Body::builder().data(self.body.clone()))
+            .map_err(|source| ureq_error(request.uri().clone(), ureq::Error::Http(source)))?;
+
+        Ok(UreqResponse::new(
+            response,
+            request.max_body_size(),
+            Arc::new(ListenBrainzParser::default()),
+        ))
+    }
+}
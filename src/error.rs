@@ -0,0 +1,573 @@
+use api_bindium::ApiRequestError;
+use api_bindium::endpoints::UriBuilderError;
+use snafu::IntoError as _;
+use snafu::Snafu;
+
+use crate::api::BaseUrlError;
+use crate::api::RequestBuildingError;
+use crate::api::submit_listens::SubmitListensError;
+use crate::api::submit_listens::validation::ListenValidationError;
+use crate::api::user::username::listen_count::ListensSnapshotError;
+#[cfg(any(feature = "sync", feature = "async"))]
+use crate::api::user::username::listens_reader::ListenFullFetchError;
+#[cfg(any(feature = "sync", feature = "async"))]
+use crate::client::cache::CacheError;
+#[cfg(any(feature = "sync", feature = "async"))]
+use crate::client::cassette::CassetteError;
+#[cfg(any(test, feature = "mock_server"))]
+use crate::mock_server::MockServerError;
+use crate::models::mbid::InvalidMbidError;
+use crate::models::token::UserTokenError;
+use crate::models::token::loading::TokenLoadingError;
+use crate::parser::ApiErrorKind;
+use crate::parser::ResponseError;
+
+/// The error type of the crate.
+///
+/// Every error of the crate can be converted into it, so applications can handle failures uniformly.
+/// See [`Error::category`] to find what went wrong without matching every variant.
+#[derive(Debug, Snafu)]
+#[snafu(visibility(pub(crate)))]
+pub enum Error {
+    /// The uri of the request couldn't be created
+    #[snafu(display("Couldn't build the uri of the request"))]
+    UriBuilderError {
+        source: UriBuilderError,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+
+    /// The body of the request couldn't be serialized
+    #[snafu(display("Couldn't serialize the body of the request"))]
+    BodySerializationError {
+        source: serde_json::Error,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+
+    /// The request couldn't be sent, or the response couldn't be read
+    #[snafu(display("Couldn't send the request"))]
+    TransportError {
+        source: ApiRequestError,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+
+    /// The server responded with an unsuccessful status, without a ListenBrainz error body
+    #[snafu(display("The server responded with the HTTP status {status}"))]
+    HttpStatusError {
+        status: u16,
+
+        /// The raw body of the response
+        body: String,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+
+    /// The response couldn't be deserialized
+    #[snafu(display("Couldn't decode the response"))]
+    JsonDecodingError {
+        source: ApiRequestError,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+
+    /// ListenBrainz refused the request
+    #[snafu(display("ListenBrainz returned an error ({code}): {message}"))]
+    ListenBrainzApiError {
+        /// The HTTP status code of the error
+        code: u16,
+
+        /// The reason given by the server
+        message: String,
+
+        kind: ApiErrorKind,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+
+    /// The user token was checked, and ListenBrainz doesn't recognize it
+    #[snafu(display("The user token is invalid: {message}"))]
+    InvalidTokenError {
+        /// The reason given by the server
+        message: String,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+
+    /// The listens are refused before being sent
+    #[snafu(display("The listens are invalid"))]
+    ListenValidationError {
+        source: ListenValidationError,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+
+    /// The checkpoint given to resume a listen fetch belongs to another fetch
+    #[snafu(display("The checkpoint doesn't match the listen fetch of `{username}`"))]
+    CheckpointMismatchError {
+        username: String,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+
+    /// An MBID is invalid
+    #[snafu(display("An MBID is invalid"))]
+    InvalidMbidError {
+        source: InvalidMbidError,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+
+    /// The base url of the endpoints is invalid
+    #[snafu(display("The base url of the endpoints is invalid"))]
+    BaseUrlError {
+        source: BaseUrlError,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+
+    /// The token couldn't be loaded from the environment or a config file
+    #[snafu(display("Couldn't load the user token"))]
+    TokenLoadingError {
+        source: TokenLoadingError,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+
+    /// The cassette couldn't be read or written
+    #[cfg(any(feature = "sync", feature = "async"))]
+    #[snafu(display("Couldn't use the cassette"))]
+    CassetteError {
+        source: CassetteError,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+
+    /// The response cache couldn't be cleared
+    #[cfg(any(feature = "sync", feature = "async"))]
+    #[snafu(display("Couldn't use the response cache"))]
+    CacheError {
+        source: CacheError,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+
+    /// The mock server couldn't be started
+    #[cfg(any(test, feature = "mock_server"))]
+    #[snafu(display("Couldn't start the mock server"))]
+    MockServerError {
+        source: MockServerError,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+}
+
+/// The broad categories of [`Error`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorCategory {
+    /// The request couldn't be created
+    RequestBuilding,
+
+    /// The request couldn't be sent, or the response couldn't be read
+    Transport,
+
+    /// The server responded with an unexpected HTTP status
+    HttpStatus,
+
+    /// The response couldn't be deserialized
+    JsonDecoding,
+
+    /// ListenBrainz refused the request with an error body
+    ServerError,
+
+    /// The user token is invalid
+    Authentication,
+
+    /// The data is refused before sending the request
+    Validation,
+
+    /// The base url or the token of the client couldn't be loaded
+    Configuration,
+
+    /// The cassette or the response cache couldn't be read or written
+    Storage,
+
+    /// The mock server couldn't be started
+    MockServer,
+}
+
+impl Error {
+    /// Return the category of the error
+    pub fn category(&self) -> ErrorCategory {
+        match self {
+            Self::UriBuilderError { .. } | Self::BodySerializationError { .. } => {
+                ErrorCategory::RequestBuilding
+            }
+            Self::TransportError { .. } => ErrorCategory::Transport,
+            // Both the token being refused by an endpoint and checked as invalid are authentication failures
+            Self::ListenBrainzApiError {
+                kind: ApiErrorKind::InvalidToken,
+                ..
+            }
+            | Self::ListenBrainzApiError {
+                code: 401 | 403, ..
+            }
+            | Self::HttpStatusError {
+                status: 401 | 403, ..
+            }
+            | Self::InvalidTokenError { .. } => ErrorCategory::Authentication,
+            Self::HttpStatusError { .. } => ErrorCategory::HttpStatus,
+            Self::JsonDecodingError { .. } => ErrorCategory::JsonDecoding,
+            Self::ListenBrainzApiError { .. } => ErrorCategory::ServerError,
+            Self::ListenValidationError { .. }
+            | Self::CheckpointMismatchError { .. }
+            | Self::InvalidMbidError { .. } => ErrorCategory::Validation,
+            Self::BaseUrlError { .. } | Self::TokenLoadingError { .. } => {
+                ErrorCategory::Configuration
+            }
+            #[cfg(any(feature = "sync", feature = "async"))]
+            Self::CassetteError { .. } | Self::CacheError { .. } => ErrorCategory::Storage,
+            #[cfg(any(test, feature = "mock_server"))]
+            Self::MockServerError { .. } => ErrorCategory::MockServer,
+        }
+    }
+
+    /// Return the kind of error returned by ListenBrainz, if the server refused the request
+    pub fn api_error_kind(&self) -> Option<ApiErrorKind> {
+        match self {
+            Self::ListenBrainzApiError { kind, .. } => Some(*kind),
+            Self::InvalidTokenError { .. } => Some(ApiErrorKind::InvalidToken),
+            Self::UriBuilderError { .. }
+            | Self::BodySerializationError { .. }
+            | Self::TransportError { .. }
+            | Self::HttpStatusError { .. }
+            | Self::JsonDecodingError { .. }
+            | Self::ListenValidationError { .. }
+            | Self::CheckpointMismatchError { .. }
+            | Self::InvalidMbidError { .. }
+            | Self::BaseUrlError { .. }
+            | Self::TokenLoadingError { .. } => None,
+            #[cfg(any(feature = "sync", feature = "async"))]
+            Self::CassetteError { .. } | Self::CacheError { .. } => None,
+            #[cfg(any(test, feature = "mock_server"))]
+            Self::MockServerError { .. } => None,
+        }
+    }
+}
+
+// === Conversions ===
+
+impl From<UriBuilderError> for Error {
+    fn from(value: UriBuilderError) -> Self {
+        UriBuilderSnafu.into_error(value)
+    }
+}
+
+impl From<RequestBuildingError> for Error {
+    fn from(value: RequestBuildingError) -> Self {
+        match value {
+            RequestBuildingError::UriBuilderError { source, .. } => source.into(),
+            RequestBuildingError::BodySerializationError { source, .. } => {
+                BodySerializationSnafu.into_error(source)
+            }
+        }
+    }
+}
+
+impl From<ApiRequestError> for Error {
+    fn from(value: ApiRequestError) -> Self {
+        if matches!(value, ApiRequestError::JsonParsingError { .. }) {
+            JsonDecodingSnafu.into_error(value)
+        } else {
+            TransportSnafu.into_error(value)
+        }
+    }
+}
+
+impl From<ResponseError> for Error {
+    fn from(value: ResponseError) -> Self {
+        match value {
+            ResponseError::ListenBrainzApiError {
+                code,
+                message,
+                kind,
+                ..
+            } => ListenBrainzApiSnafu {
+                code,
+                message,
+                kind,
+            }
+            .build(),
+            ResponseError::HttpStatusError { status, body, .. } => {
+                HttpStatusSnafu { status, body }.build()
+            }
+            ResponseError::ApiRequestError { source, .. } => source.into(),
+        }
+    }
+}
+
+impl From<ListenValidationError> for Error {
+    fn from(value: ListenValidationError) -> Self {
+        ListenValidationSnafu.into_error(value)
+    }
+}
+
+impl From<InvalidMbidError> for Error {
+    fn from(value: InvalidMbidError) -> Self {
+        InvalidMbidSnafu.into_error(value)
+    }
+}
+
+impl From<BaseUrlError> for Error {
+    fn from(value: BaseUrlError) -> Self {
+        BaseUrlSnafu.into_error(value)
+    }
+}
+
+impl From<TokenLoadingError> for Error {
+    fn from(value: TokenLoadingError) -> Self {
+        TokenLoadingSnafu.into_error(value)
+    }
+}
+
+#[cfg(any(feature = "sync", feature = "async"))]
+impl From<CassetteError> for Error {
+    fn from(value: CassetteError) -> Self {
+        CassetteSnafu.into_error(value)
+    }
+}
+
+#[cfg(any(feature = "sync", feature = "async"))]
+impl From<CacheError> for Error {
+    fn from(value: CacheError) -> Self {
+        CacheSnafu.into_error(value)
+    }
+}
+
+#[cfg(any(test, feature = "mock_server"))]
+impl From<MockServerError> for Error {
+    fn from(value: MockServerError) -> Self {
+        MockServerSnafu.into_error(value)
+    }
+}
+
+impl From<SubmitListensError> for Error {
+    fn from(value: SubmitListensError) -> Self {
+        match value {
+            SubmitListensError::RequestBuildingError { source, .. } => source.into(),
+            SubmitListensError::ValidationError { source, .. } => source.into(),
+        }
+    }
+}
+
+impl From<ListensSnapshotError> for Error {
+    fn from(value: ListensSnapshotError) -> Self {
+        match value {
+            ListensSnapshotError::UriBuilderError { source, .. } => source.into(),
+            ListensSnapshotError::ApiRequestError { source, .. } => source.into(),
+            ListensSnapshotError::ResponseError { source, .. } => source.into(),
+        }
+    }
+}
+
+impl From<UserTokenError> for Error {
+    fn from(value: UserTokenError) -> Self {
+        match value {
+            UserTokenError::UriBuilderError { source, .. } => source.into(),
+            UserTokenError::ApiRequestError { source, .. } => source.into(),
+            UserTokenError::ResponseError { source, .. } => source.into(),
+            // The validation response is a 200 even when the token is invalid, so it isn't a server error
+            UserTokenError::InvalidToken { response, .. } => InvalidTokenSnafu {
+                message: response.message,
+            }
+            .build(),
+        }
+    }
+}
+
+/// The listens fetched before an interruption are lost. Use [`ListenFullFetchError::into_partial`] beforehand to keep them
+#[cfg(any(feature = "sync", feature = "async"))]
+impl From<ListenFullFetchError> for Error {
+    fn from(value: ListenFullFetchError) -> Self {
+        match value {
+            ListenFullFetchError::ApiRequestError { source, .. } => source.into(),
+            ListenFullFetchError::UriBuilderError { source, .. } => source.into(),
+            ListenFullFetchError::ParserError { source, .. } => source.into(),
+            ListenFullFetchError::Interrupted { source, .. } => (*source).into(),
+            ListenFullFetchError::CheckpointMismatch { username, .. } => {
+                CheckpointMismatchSnafu { username }.build()
+            }
+        }
+    }
+}
+
+#[cfg(any(feature = "sync", feature = "async"))]
+impl From<crate::api::user::username::listens_sync::ListenSyncError> for Error {
+    fn from(value: crate::api::user::username::listens_sync::ListenSyncError) -> Self {
+        use crate::api::user::username::listens_sync::ListenSyncError;
+
+        match value {
+            ListenSyncError::ListenFullFetchError { source, .. } => source.into(),
+            ListenSyncError::ListensSnapshotError { source, .. } => source.into(),
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl From<crate::api::submit_listens::import::ListenBatchError> for Error {
+    fn from(value: crate::api::submit_listens::import::ListenBatchError) -> Self {
+        use crate::api::submit_listens::import::ListenBatchError;
+
+        match value {
+            ListenBatchError::SubmitListensError { source, .. } => source.into(),
+            ListenBatchError::ApiRequestError { source, .. } => source.into(),
+            ListenBatchError::ResponseError { source, .. } => source.into(),
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl From<crate::api::delete_listen::bulk::ListenDeletionError> for Error {
+    fn from(value: crate::api::delete_listen::bulk::ListenDeletionError) -> Self {
+        use crate::api::delete_listen::bulk::ListenDeletionError;
+
+        match value {
+            ListenDeletionError::RequestBuildingError { source, .. } => source.into(),
+            ListenDeletionError::ApiRequestError { source, .. } => source.into(),
+            ListenDeletionError::ResponseError { source, .. } => source.into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::api::ListenBrainzAPIEnpoints;
+    use crate::api::validate_token::ValidateTokenResponse;
+    use crate::error::Error;
+    use crate::error::ErrorCategory;
+    use crate::models::mbid::RecordingMbid;
+    use crate::models::token::InvalidTokenSnafu;
+    use crate::models::token::UserToken;
+    use crate::parser::ApiErrorKind;
+    use crate::parser::ListenBrainzApiSnafu;
+
+    #[test]
+    fn error_conversion_test() {
+        let err: Error = ListenBrainzApiSnafu {
+            code: 401_u16,
+            message: "Invalid authorization token.",
+            kind: ApiErrorKind::InvalidToken,
+        }
+        .build()
+        .into();
+
+        assert_eq!(err.category(), ErrorCategory::Authentication);
+        assert_eq!(err.api_error_kind(), Some(ApiErrorKind::InvalidToken));
+
+        let err: Error = ListenBrainzApiSnafu {
+            code: 403_u16,
+            message: "Forbidden",
+            kind: ApiErrorKind::Other,
+        }
+        .build()
+        .into();
+        assert_eq!(err.category(), ErrorCategory::Authentication);
+
+        let err: Error = ListenBrainzApiSnafu {
+            code: 404_u16,
+            message: "Cannot find user: abc",
+            kind: ApiErrorKind::UserNotFound,
+        }
+        .build()
+        .into();
+        assert_eq!(err.category(), ErrorCategory::ServerError);
+
+        let err: Error = InvalidTokenSnafu {
+            response: ValidateTokenResponse {
+                code: 200,
+                message: "Token invalid.".to_string(),
+                valid: false,
+                user_name: None,
+            },
+        }
+        .build()
+        .into();
+        assert_eq!(err.category(), ErrorCategory::Authentication);
+        assert_eq!(err.api_error_kind(), Some(ApiErrorKind::InvalidToken));
+
+        let err: Error = RecordingMbid::try_from("not an mbid").unwrap_err().into();
+        assert_eq!(err.category(), ErrorCategory::Validation);
+
+        let err: Error = ListenBrainzAPIEnpoints::from_base_url("localhost:8100")
+            .unwrap_err()
+            .into();
+        assert_eq!(err.category(), ErrorCategory::Configuration);
+
+        let err: Error = UserToken::from_dotfile("/nonexistent/.listenbrainz_token")
+            .unwrap_err()
+            .into();
+        assert_eq!(err.category(), ErrorCategory::Configuration);
+        assert_eq!(err.api_error_kind(), None);
+    }
+}
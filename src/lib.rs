@@ -5,10 +5,15 @@
 
 pub mod api;
 pub mod client;
-#[cfg(feature = "async")]
+pub mod error;
+#[cfg(any(feature = "sync", feature = "async"))]
 mod inner_macros;
+#[cfg(any(test, feature = "mock_server"))]
+pub mod mock_server;
 pub mod models;
+pub mod parser;
 
 pub use crate::api::ListenBrainzAPIEnpoints;
 pub use crate::client::ListenBrainzClient;
+pub use crate::error::Error;
 pub use api_bindium;
@@ -0,0 +1,247 @@
+use std::collections::HashMap;
+use std::collections::VecDeque;
+
+use serde_json::json;
+
+use crate::api::user::username::listens::UserListensListen;
+use crate::api::user::username::listens::UserListensMBIDMapping;
+use crate::api::user::username::listens::UserListensTrackMetadata;
+use crate::models::mbid::RecordingMbid;
+use crate::models::mbid::RecordingMsid;
+
+/// The name of the user loaded by [`MockData::fixtures`]
+pub const FIXTURE_USER: &str = "RustyNova";
+
+/// The token of [`FIXTURE_USER`]
+pub const FIXTURE_TOKEN: &str = "mock-token";
+
+/// The recording mapped to a third of the fixture listens
+pub const FIXTURE_RECORDING_MBID: &str = "61c54b0e-3a82-49af-9cc7-73ff34365697";
+
+/// The release returned in the fresh releases of [`FIXTURE_USER`]
+pub const FIXTURE_FRESH_RELEASE_MBID: &str = "4a3b8e5f-1c2d-4e6f-8a9b-0c1d2e3f4a5b";
+
+/// The release group of [`FIXTURE_FRESH_RELEASE_MBID`]
+pub const FIXTURE_FRESH_RELEASE_GROUP_MBID: &str = "9f8e7d6c-5b4a-4392-8170-6f5e4d3c2b1a";
+
+/// The artist of [`FIXTURE_FRESH_RELEASE_MBID`]
+pub const FIXTURE_FRESH_RELEASE_ARTIST_MBID: &str = "056e4f3e-d505-4dad-8ec1-d04f521cbb56";
+
+/// The number of fixture listens between [`FIXTURE_LISTENS_START`] and [`FIXTURE_LISTENS_END`]
+pub const FIXTURE_LISTEN_COUNT: usize = 4840;
+
+/// All the generated fixture listens are listened after this timestamp
+pub const FIXTURE_LISTENS_START: i64 = 1_705_000_000;
+
+/// All the generated fixture listens are listened before this timestamp
+pub const FIXTURE_LISTENS_END: i64 = 1_710_000_000;
+
+/// The time between two generated fixture listens, in seconds
+const FIXTURE_LISTEN_INTERVAL: i64 = 1033;
+
+/// A single listen outside of the generated period, with a known msid
+const FIXTURE_EXTRA_LISTEN: (i64, &str) = (1_763_396_996, "cfb002e7-f093-4678-8bf7-fb139a4f718c");
+
+/// The tracks of the generated listens: (artist, track, release)
+const FIXTURE_TRACKS: [(&str, &str, &str); 3] = [
+    (
+        "Rick Astley",
+        "Never Gonna Give You Up",
+        "Whenever You Need Somebody",
+    ),
+    ("Daft Punk", "One More Time", "Discovery"),
+    ("Nightwish", "Ghost Love Score", "Once"),
+];
+
+/// The data served by a [`crate::mock_server::MockListenBrainzServer`]
+#[derive(Debug, Clone, Default)]
+pub struct MockData {
+    /// The users of the server, by user name
+    pub users: HashMap<String, MockUser>,
+
+    /// Statuses returned to the next requests instead of their response, to simulate temporary failures of the server
+    pub queued_errors: VecDeque<u16>,
+
+    /// The seconds sent in the `X-RateLimit-Reset-In` header. `None` sends the default of 10 seconds
+    pub rate_limit_reset_in: Option<u64>,
+
+    /// The seconds sent in the `Retry-After` header of the 503 responses. `None` leaves the header out
+    pub retry_after: Option<u64>,
+
+    /// Also return the listens listened at `min_ts` and `max_ts`, so the windows of a full fetch overlap on their bounds and return duplicates
+    pub inclusive_listen_bounds: bool,
+
+    /// The number of msids generated so far
+    generated_msids: usize,
+}
+
+impl MockData {
+    /// Create the fixture data: the user [`FIXTURE_USER`] with [`FIXTURE_LISTEN_COUNT`] listens between
+    /// [`FIXTURE_LISTENS_START`] and [`FIXTURE_LISTENS_END`], and one more recent listen
+    pub fn fixtures() -> Self {
+        let mut user = MockUser::new(FIXTURE_TOKEN);
+
+        for i in 0..FIXTURE_LISTEN_COUNT {
+            let (artist_name, track_name, release_name) = FIXTURE_TRACKS[i % FIXTURE_TRACKS.len()];
+            let listened_at =
+                FIXTURE_LISTENS_START + 1 + i64::try_from(i).unwrap() * FIXTURE_LISTEN_INTERVAL;
+
+            let mbid_mapping = (i % FIXTURE_TRACKS.len() == 0).then(|| UserListensMBIDMapping {
+                artist_mbids: None,
+                artists: None,
+                recording_mbid: RecordingMbid::try_from(FIXTURE_RECORDING_MBID).unwrap(),
+                recording_name: Some(track_name.to_string()),
+                caa_id: None,
+                caa_release_mbid: None,
+                release_mbid: None,
+            });
+
+            user.listens.push(UserListensListen {
+                user_name: FIXTURE_USER.to_string(),
+                inserted_at: listened_at,
+                listened_at,
+                recording_msid: generated_msid(i),
+                track_metadata: UserListensTrackMetadata {
+                    artist_name: artist_name.to_string(),
+                    track_name: track_name.to_string(),
+                    release_name: Some(release_name.to_string()),
+                    additional_info: HashMap::new(),
+                    mbid_mapping,
+                },
+            });
+        }
+
+        let (listened_at, msid) = FIXTURE_EXTRA_LISTEN;
+        let (artist_name, track_name, release_name) = FIXTURE_TRACKS[0];
+        user.listens.push(UserListensListen {
+            user_name: FIXTURE_USER.to_string(),
+            inserted_at: listened_at,
+            listened_at,
+            recording_msid: RecordingMsid::try_from(msid).unwrap(),
+            track_metadata: UserListensTrackMetadata {
+                artist_name: artist_name.to_string(),
+                track_name: track_name.to_string(),
+                release_name: Some(release_name.to_string()),
+                additional_info: HashMap::new(),
+                mbid_mapping: None,
+            },
+        });
+
+        user.fresh_releases.push(json!({
+            "artist_credit_name": "Daft Punk",
+            "artist_mbids": [FIXTURE_FRESH_RELEASE_ARTIST_MBID],
+            "caa_id": null,
+            "caa_release_mbid": FIXTURE_FRESH_RELEASE_MBID,
+            "confidence": 7,
+            "listen_count": 42,
+            "release_date": "2024-03-01",
+            "release_group_mbid": FIXTURE_FRESH_RELEASE_GROUP_MBID,
+            "release_group_primary_type": "Album",
+            "release_group_secondary_type": null,
+            "release_mbid": FIXTURE_FRESH_RELEASE_MBID,
+            "release_name": "Discovery",
+            "release_tags": ["electronic"],
+        }));
+
+        Self {
+            users: HashMap::from([(FIXTURE_USER.to_string(), user)]),
+            queued_errors: VecDeque::new(),
+            rate_limit_reset_in: None,
+            retry_after: None,
+            inclusive_listen_bounds: false,
+            generated_msids: FIXTURE_LISTEN_COUNT,
+        }
+    }
+
+    /// Generate the msid of a new listen
+    pub fn new_msid(&mut self) -> RecordingMsid {
+        self.generated_msids += 1;
+        generated_msid(self.generated_msids)
+    }
+
+    /// Find the user owning the token
+    pub fn user_by_token(&mut self, token: &str) -> Option<(&str, &mut MockUser)> {
+        self.users
+            .iter_mut()
+            .find(|(_, user)| user.token == token)
+            .map(|(name, user)| (name.as_str(), user))
+    }
+
+    /// Return the total number of listens and listeners of a recording
+    pub fn recording_popularity(&self, recording_mbid: &RecordingMbid) -> (u64, u64) {
+        let mut listen_count = 0;
+        let mut user_count = 0;
+
+        for user in self.users.values() {
+            let count = user
+                .listens
+                .iter()
+                .filter(|listen| {
+                    listen
+                        .track_metadata
+                        .mbid_mapping
+                        .as_ref()
+                        .is_some_and(|mapping| &mapping.recording_mbid == recording_mbid)
+                })
+                .count() as u64;
+
+            listen_count += count;
+            if count > 0 {
+                user_count += 1;
+            }
+        }
+
+        (listen_count, user_count)
+    }
+}
+
+/// A user of the mock server
+#[derive(Debug, Clone)]
+pub struct MockUser {
+    /// The token of the user
+    pub token: String,
+
+    /// The listens of the user. They don't need to be sorted
+    pub listens: Vec<UserListensListen>,
+
+    /// The track the user is currently listening to
+    pub playing_now: Option<UserListensTrackMetadata>,
+
+    /// The fresh releases of the user. They are kept as raw JSON, so invalid releases can be served
+    pub fresh_releases: Vec<serde_json::Value>,
+}
+
+impl MockUser {
+    /// Create a user without listens
+    pub fn new(token: impl Into<String>) -> Self {
+        Self {
+            token: token.into(),
+            listens: Vec::new(),
+            playing_now: None,
+            fresh_releases: Vec::new(),
+        }
+    }
+
+    /// The timestamp of the latest listen of the user, or 0 if they haven't listened to anything
+    pub fn latest_listen_ts(&self) -> i64 {
+        self.listens
+            .iter()
+            .map(|listen| listen.listened_at)
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// The timestamp of the oldest listen of the user, or 0 if they haven't listened to anything
+    pub fn oldest_listen_ts(&self) -> i64 {
+        self.listens
+            .iter()
+            .map(|listen| listen.listened_at)
+            .min()
+            .unwrap_or_default()
+    }
+}
+
+/// Create a deterministic msid for the n-th generated listen
+fn generated_msid(n: usize) -> RecordingMsid {
+    RecordingMsid::try_from(format!("00000000-0000-4000-8000-{n:012x}")).unwrap()
+}
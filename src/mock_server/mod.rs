@@ -0,0 +1,311 @@
+//! A local stand-in of the ListenBrainz api, to run tests without network access.
+//!
+//! ```no_run
+//! use listenbrainz_rs::mock_server::MockListenBrainzServer;
+//!
+//! let server = MockListenBrainzServer::start_with_fixtures().unwrap();
+//! let client = server.client();
+//! // Use the client as usual. The requests are answered by the mock server
+//! ```
+use core::hash::Hash as _;
+use core::hash::Hasher as _;
+use core::net::SocketAddr;
+use core::sync::atomic::AtomicUsize;
+use core::sync::atomic::Ordering;
+use std::hash::DefaultHasher;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::MutexGuard;
+use std::thread::JoinHandle;
+
+use snafu::OptionExt as _;
+use snafu::ResultExt as _;
+use snafu::Snafu;
+use tiny_http::Header;
+use tiny_http::Request;
+use tiny_http::Response;
+use tiny_http::Server;
+
+use crate::api::ListenBrainzAPIEnpoints;
+use crate::client::ListenBrainzClient;
+use crate::mock_server::data::MockData;
+use crate::mock_server::routes::MockRequest;
+
+pub mod data;
+mod routes;
+
+/// The seconds sent in the `X-RateLimit-Reset-In` header, unless [`MockData::rate_limit_reset_in`] is set
+const DEFAULT_RATE_LIMIT_RESET_IN: u64 = 10;
+
+/// A ListenBrainz server running locally on a background thread.
+///
+/// It only implements the endpoints of the crate, with data from a [`MockData`].
+/// The server is stopped when dropped.
+pub struct MockListenBrainzServer {
+    addr: SocketAddr,
+    server: Arc<Server>,
+    data: Arc<Mutex<MockData>>,
+    requests: Arc<AtomicUsize>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl MockListenBrainzServer {
+    /// Start a server without any user
+    pub fn start() -> Result<Self, MockServerError> {
+        Self::start_with_data(MockData::default())
+    }
+
+    /// Start a server loaded with [`MockData::fixtures`]
+    pub fn start_with_fixtures() -> Result<Self, MockServerError> {
+        Self::start_with_data(MockData::fixtures())
+    }
+
+    /// Start a server serving the provided data, on a random local port
+    pub fn start_with_data(data: MockData) -> Result<Self, MockServerError> {
+        let server = Server::http("127.0.0.1:0").context(BindSnafu)?;
+        let addr = server.server_addr().to_ip().context(NotIpSnafu)?;
+
+        let server = Arc::new(server);
+        let data = Arc::new(Mutex::new(data));
+        let requests = Arc::new(AtomicUsize::new(0));
+
+        let handle = {
+            let server = server.clone();
+            let data = data.clone();
+            let requests = requests.clone();
+            std::thread::spawn(move || {
+                for request in server.incoming_requests() {
+                    requests.fetch_add(1, Ordering::Relaxed);
+                    respond(&data, request);
+                }
+            })
+        };
+
+        Ok(Self {
+            addr,
+            server,
+            data,
+            requests,
+            handle: Some(handle),
+        })
+    }
+
+    /// The address the server listens on
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Access the data of the server, to add fixtures or check what got submitted
+    pub fn data(&self) -> MutexGuard<'_, MockData> {
+        self.data.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    /// The number of requests received so far
+    pub fn request_count(&self) -> usize {
+        self.requests.load(Ordering::Relaxed)
+    }
+
+    /// Endpoints pointing to this server
+    pub fn endpoints(&self) -> ListenBrainzAPIEnpoints {
+        ListenBrainzAPIEnpoints::builder()
+            .scheme("http".to_string())
+            .lb_domain(self.addr.to_string())
+            .build()
+    }
+
+    /// A client sending its requests to this server
+    pub fn client(&self) -> ListenBrainzClient {
+        ListenBrainzClient::builder()
+            .endpoints(self.endpoints())
+            .build()
+    }
+}
+
+impl core::fmt::Debug for MockListenBrainzServer {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("MockListenBrainzServer")
+            .field("addr", &self.addr)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Drop for MockListenBrainzServer {
+    fn drop(&mut self) {
+        self.server.unblock();
+
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+/// Answer a single request
+fn respond(data: &Mutex<MockData>, mut request: Request) {
+    let mut body = String::new();
+    if request.as_reader().read_to_string(&mut body).is_err() {
+        body.clear();
+    }
+
+    let token = request
+        .headers()
+        .iter()
+        .find(|header| header.field.equiv("Authorization"))
+        .and_then(|header| header.value.as_str().strip_prefix("Token "))
+        .map(ToString::to_string);
+    let if_none_match = request
+        .headers()
+        .iter()
+        .find(|header| header.field.equiv("If-None-Match"))
+        .map(|header| header.value.to_string());
+    let method = request.method().as_str().to_string();
+    let url = request.url().to_string();
+
+    let (response, reset_in, retry_after) = {
+        let mut data = data.lock().unwrap_or_else(|err| err.into_inner());
+
+        let response = routes::handle(
+            &mut data,
+            &MockRequest {
+                method: &method,
+                url: &url,
+                token: token.as_deref(),
+                body: &body,
+            },
+        );
+
+        (
+            response,
+            data.rate_limit_reset_in
+                .unwrap_or(DEFAULT_RATE_LIMIT_RESET_IN),
+            data.retry_after,
+        )
+    };
+
+    let body = response.body.to_string();
+
+    // Only the successful GET requests get an ETag
+    let etag = (method == "GET" && response.status == 200).then(|| etag(&body));
+    let not_modified = etag.is_some() && etag == if_none_match;
+
+    let mut response = if not_modified {
+        Response::from_string(String::new()).with_status_code(304)
+    } else {
+        Response::from_string(body).with_status_code(response.status)
+    };
+    if let Some(etag) = &etag {
+        response.add_header(header("ETag", etag));
+    }
+    if let Some(retry_after) = retry_after.filter(|_| response.status_code().0 == 503) {
+        response.add_header(header("Retry-After", &retry_after.to_string()));
+    }
+
+    let response = response
+        .with_header(header("Content-Type", "application/json"))
+        .with_header(header("X-RateLimit-Limit", "1000"))
+        .with_header(header("X-RateLimit-Remaining", "1000"))
+        .with_header(header("X-RateLimit-Reset-In", &reset_in.to_string()));
+
+    // The client may have given up on the request. Nothing to do about it
+    let _ = request.respond(response);
+}
+
+fn etag(body: &str) -> String {
+    let mut hasher = DefaultHasher::new();
+    body.hash(&mut hasher);
+
+    format!("\"{:016x}\"", hasher.finish())
+}
+
+fn header(field: &str, value: &str) -> Header {
+    Header::from_bytes(field.as_bytes(), value.as_bytes())
+        .expect("The header should be valid ASCII")
+}
+
+#[derive(Debug, Snafu)]
+pub enum MockServerError {
+    #[snafu(display("Couldn't bind the mock server to a local port"))]
+    BindError {
+        source: Box<dyn core::error::Error + Send + Sync>,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+
+    #[snafu(display("The mock server isn't listening on an IP address"))]
+    NotIpError {
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+}
+
+#[cfg(test)]
+#[cfg(feature = "async")]
+mod test {
+    use macro_rules_attribute::apply;
+
+    use crate::api::submit_listens::SubmitListensPayload;
+    use crate::api::submit_listens::SubmittedListen;
+    use crate::api::submit_listens::SubmittedTrackMetadata;
+    use crate::error::ErrorCategory;
+    use crate::mock_server::MockListenBrainzServer;
+    use crate::mock_server::data::FIXTURE_TOKEN;
+    use crate::mock_server::data::FIXTURE_USER;
+    use crate::models::token::UserToken;
+    use crate::parser::ApiErrorKind;
+
+    #[apply(smol_macros::test!)]
+    async fn submit_listens_test() {
+        let server = MockListenBrainzServer::start_with_fixtures().unwrap();
+        let client = server.client();
+
+        let listen = SubmittedListen::builder()
+            .listened_at(1_800_000_000)
+            .track_metadata(
+                SubmittedTrackMetadata::builder()
+                    .artist_name("Kikuo")
+                    .track_name("Aishite")
+                    .build(),
+            )
+            .build();
+
+        let err = client
+            .post_submit_listens_async(
+                SubmitListensPayload::Single(listen.clone()),
+                UserToken::from("wrong".to_string()),
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(err.api_error_kind(), Some(ApiErrorKind::InvalidToken));
+
+        client
+            .post_submit_listens_async(
+                SubmitListensPayload::Single(listen),
+                UserToken::from(FIXTURE_TOKEN.to_string()),
+            )
+            .await
+            .unwrap();
+
+        let res = client
+            .get_user_username_listens_async()
+            .username(FIXTURE_USER)
+            .count(1)
+            .call()
+            .await
+            .unwrap();
+        assert_eq!(res.payload.listens[0].track_metadata.track_name, "Aishite");
+        assert_eq!(res.payload.latest_listen_ts, 1_800_000_000);
+
+        let err = client
+            .get_user_username_listen_count_async("nobody")
+            .await
+            .unwrap_err();
+        assert_eq!(err.category(), ErrorCategory::ServerError);
+        assert_eq!(err.api_error_kind(), Some(ApiErrorKind::UserNotFound));
+    }
+}
@@ -0,0 +1,308 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+use serde_json::Value;
+use serde_json::json;
+
+use crate::api::delete_listen::DeleteListenBody;
+use crate::api::submit_listens::SubmittedListen;
+use crate::api::user::username::listens::UserListensListen;
+use crate::api::user::username::listens::UserListensTrackMetadata;
+use crate::mock_server::data::MockData;
+use crate::mock_server::data::MockUser;
+use crate::models::mbid::RecordingMbid;
+use crate::models::timestamp::Timestamp;
+
+/// The default number of listens returned by the `listens` endpoint
+const DEFAULT_LISTEN_COUNT: usize = 25;
+
+/// The maximum number of listens returned by the `listens` endpoint
+const MAX_LISTEN_COUNT: usize = 1000;
+
+/// A request received by the mock server
+pub(super) struct MockRequest<'a> {
+    pub method: &'a str,
+    pub url: &'a str,
+
+    /// The token of the `Authorization` header, if any
+    pub token: Option<&'a str>,
+    pub body: &'a str,
+}
+
+/// The response to a [`MockRequest`]
+pub(super) struct MockResponse {
+    pub status: u16,
+    pub body: Value,
+}
+
+impl MockResponse {
+    fn ok(body: Value) -> Self {
+        Self { status: 200, body }
+    }
+
+    /// A ListenBrainz error body
+    fn error(status: u16, message: &str) -> Self {
+        Self {
+            status,
+            body: json!({ "code": status, "error": message }),
+        }
+    }
+}
+
+/// The result of an endpoint. Both sides are sent back to the client, the error side only allows early returns with `?`
+type MockResult = Result<MockResponse, MockResponse>;
+
+/// Route the request to its endpoint
+pub(super) fn handle(data: &mut MockData, request: &MockRequest<'_>) -> MockResponse {
+    if let Some(status) = data.queued_errors.pop_front() {
+        return MockResponse::error(status, "Simulated failure of the mock server");
+    }
+
+    let (path, query) = request.url.split_once('?').unwrap_or((request.url, ""));
+    let query = parse_query(query);
+    let segments = path.trim_matches('/').split('/').collect::<Vec<_>>();
+
+    let result = match (request.method, segments.as_slice()) {
+        ("GET", ["1", "user", username, "listens"]) => get_listens(data, username, &query),
+        ("GET", ["1", "user", username, "listen-count"]) => get_listen_count(data, username),
+        ("GET", ["1", "user", username, "playing-now"]) => get_playing_now(data, username),
+        ("GET", ["1", "user", username, "fresh_releases"]) => get_fresh_releases(data, username),
+        ("GET", ["1", "validate-token"]) => Ok(validate_token(data, request.token)),
+        ("POST", ["1", "submit-listens"]) => submit_listens(data, request),
+        ("POST", ["1", "delete-listen"]) => delete_listen(data, request),
+        ("POST", ["1", "popularity", "recording"]) => popularity_recording(data, request.body),
+        _ => Err(MockResponse::error(
+            404,
+            "The requested URL was not found on the server.",
+        )),
+    };
+
+    result.unwrap_or_else(|response| response)
+}
+
+fn parse_query(query: &str) -> HashMap<&str, &str> {
+    query
+        .split('&')
+        .filter_map(|param| param.split_once('='))
+        .collect()
+}
+
+/// Parse an optional query parameter
+fn query_param<T: core::str::FromStr>(
+    query: &HashMap<&str, &str>,
+    key: &str,
+) -> Result<Option<T>, MockResponse> {
+    query
+        .get(key)
+        .map(|value| value.parse::<T>())
+        .transpose()
+        .map_err(|_| MockResponse::error(400, &format!("Invalid value for `{key}`")))
+}
+
+fn parse_body<'a, T: Deserialize<'a>>(body: &'a str) -> Result<T, MockResponse> {
+    serde_json::from_str(body)
+        .map_err(|_| MockResponse::error(400, "Invalid JSON document submitted."))
+}
+
+fn find_user<'a>(data: &'a MockData, username: &str) -> Result<&'a MockUser, MockResponse> {
+    data.users
+        .get(username)
+        .ok_or_else(|| MockResponse::error(404, &format!("Cannot find user: {username}")))
+}
+
+fn authenticate<'a>(
+    data: &'a mut MockData,
+    token: Option<&str>,
+) -> Result<(&'a str, &'a mut MockUser), MockResponse> {
+    token
+        .and_then(|token| data.user_by_token(token))
+        .ok_or_else(|| MockResponse::error(401, "Invalid authorization token."))
+}
+
+fn get_listens(data: &MockData, username: &str, query: &HashMap<&str, &str>) -> MockResult {
+    let user = find_user(data, username)?;
+    let min_ts = query_param::<i64>(query, "min_ts")?;
+    let max_ts = query_param::<i64>(query, "max_ts")?;
+    let count = query_param::<usize>(query, "count")?
+        .unwrap_or(DEFAULT_LISTEN_COUNT)
+        .min(MAX_LISTEN_COUNT);
+
+    let inclusive = data.inclusive_listen_bounds;
+
+    let mut listens = user
+        .listens
+        .iter()
+        .filter(|listen| {
+            min_ts.is_none_or(|min_ts| {
+                listen.listened_at > min_ts || (inclusive && listen.listened_at == min_ts)
+            })
+        })
+        .filter(|listen| {
+            max_ts.is_none_or(|max_ts| {
+                listen.listened_at < max_ts || (inclusive && listen.listened_at == max_ts)
+            })
+        })
+        .collect::<Vec<_>>();
+    listens.sort_by_key(|listen| core::cmp::Reverse(listen.listened_at));
+    listens.truncate(count);
+
+    Ok(MockResponse::ok(json!({
+        "payload": {
+            "count": listens.len(),
+            "latest_listen_ts": user.latest_listen_ts(),
+            "oldest_listen_ts": user.oldest_listen_ts(),
+            "user_id": username,
+            "listens": listens,
+        }
+    })))
+}
+
+fn get_listen_count(data: &MockData, username: &str) -> MockResult {
+    let user = find_user(data, username)?;
+
+    Ok(MockResponse::ok(
+        json!({ "payload": { "count": user.listens.len() } }),
+    ))
+}
+
+fn get_playing_now(data: &MockData, username: &str) -> MockResult {
+    let user = find_user(data, username)?;
+
+    let listens = user
+        .playing_now
+        .iter()
+        .map(|track_metadata| json!({ "playing_now": true, "track_metadata": track_metadata }))
+        .collect::<Vec<_>>();
+
+    Ok(MockResponse::ok(json!({
+        "payload": {
+            "count": listens.len(),
+            "user_id": username,
+            "playing_now": !listens.is_empty(),
+            "listens": listens,
+        }
+    })))
+}
+
+fn get_fresh_releases(data: &MockData, username: &str) -> MockResult {
+    let user = find_user(data, username)?;
+
+    Ok(MockResponse::ok(
+        json!({ "payload": { "user_id": username, "releases": user.fresh_releases } }),
+    ))
+}
+
+fn validate_token(data: &mut MockData, token: Option<&str>) -> MockResponse {
+    match token.and_then(|token| data.user_by_token(token)) {
+        Some((user_name, _)) => MockResponse::ok(json!({
+            "code": 200,
+            "message": "Token valid.",
+            "valid": true,
+            "user_name": user_name,
+        })),
+        // Like ListenBrainz, the user name is left out for invalid tokens
+        None => MockResponse::ok(json!({
+            "code": 200,
+            "message": "Token invalid.",
+            "valid": false,
+        })),
+    }
+}
+
+#[derive(Deserialize)]
+struct SubmitListensBody {
+    listen_type: String,
+    payload: Vec<SubmittedListen>,
+}
+
+fn submit_listens(data: &mut MockData, request: &MockRequest<'_>) -> MockResult {
+    let body = parse_body::<SubmitListensBody>(request.body)?;
+    let user_name = authenticate(data, request.token)?.0.to_string();
+
+    let mut new_listens = Vec::with_capacity(body.payload.len());
+    for listen in body.payload {
+        let listened_at = match (body.listen_type.as_str(), listen.listened_at) {
+            ("playing_now", None) => None,
+            ("single" | "import", Some(listened_at)) => Some(listened_at),
+            ("playing_now", Some(_)) => {
+                return Err(MockResponse::error(
+                    400,
+                    "playing_now listens may not have listened_at.",
+                ));
+            }
+            ("single" | "import", None) => {
+                return Err(MockResponse::error(
+                    400,
+                    "JSON document must contain the key listened_at.",
+                ));
+            }
+            _ => return Err(MockResponse::error(400, "Invalid listen_type.")),
+        };
+
+        let track_metadata = UserListensTrackMetadata {
+            artist_name: listen.track_metadata.artist_name,
+            track_name: listen.track_metadata.track_name,
+            release_name: listen.track_metadata.release_name,
+            additional_info: listen.track_metadata.additional_info.unwrap_or_default(),
+            mbid_mapping: None,
+        };
+
+        new_listens.push((listened_at, track_metadata, data.new_msid()));
+    }
+
+    let inserted_at = Timestamp::now().as_secs();
+    let user = data
+        .users
+        .get_mut(&user_name)
+        .expect("The user got authenticated");
+    for (listened_at, track_metadata, recording_msid) in new_listens {
+        match listened_at {
+            Some(listened_at) => user.listens.push(UserListensListen {
+                user_name: user_name.clone(),
+                inserted_at,
+                listened_at,
+                recording_msid,
+                track_metadata,
+            }),
+            None => user.playing_now = Some(track_metadata),
+        }
+    }
+
+    Ok(MockResponse::ok(json!({ "status": "ok" })))
+}
+
+fn delete_listen(data: &mut MockData, request: &MockRequest<'_>) -> MockResult {
+    let body = parse_body::<DeleteListenBody>(request.body)?;
+    let (_, user) = authenticate(data, request.token)?;
+
+    user.listens.retain(|listen| {
+        listen.listened_at != body.listened_at || listen.recording_msid != body.recording_msid
+    });
+
+    Ok(MockResponse::ok(json!({ "status": "ok" })))
+}
+
+#[derive(Deserialize)]
+struct PopularityRecordingBody {
+    recording_mbids: Vec<RecordingMbid>,
+}
+
+fn popularity_recording(data: &MockData, body: &str) -> MockResult {
+    let body = parse_body::<PopularityRecordingBody>(body)?;
+
+    let popularity = body
+        .recording_mbids
+        .iter()
+        .map(|recording_mbid| {
+            let (listen_count, user_count) = data.recording_popularity(recording_mbid);
+
+            json!({
+                "recording_mbid": recording_mbid,
+                "total_listen_count": (listen_count > 0).then_some(listen_count),
+                "total_user_count": (user_count > 0).then_some(user_count),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    Ok(MockResponse::ok(Value::Array(popularity)))
+}
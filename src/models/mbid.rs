@@ -0,0 +1,144 @@
+use core::fmt::Display;
+use core::str::FromStr;
+
+use serde::Deserialize;
+use serde::Serialize;
+use snafu::Snafu;
+use snafu::ensure;
+
+/// Return true if the string has the format of an MBID (An UUID in its hyphenated form)
+pub fn is_valid_mbid(mbid: &str) -> bool {
+    mbid.len() == 36
+        && mbid.char_indices().all(|(i, c)| match i {
+            8 | 13 | 18 | 23 => c == '-',
+            _ => c.is_ascii_hexdigit(),
+        })
+}
+
+/// Create a validated UUID based identifier type
+macro_rules! uuid_newtype {
+    ($(#[$meta:meta])* $name: ident) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+        #[serde(try_from = "String", into = "String")]
+        pub struct $name(String);
+
+        impl $name {
+            /// Return the identifier as a string slice
+            pub fn as_str(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl TryFrom<String> for $name {
+            type Error = InvalidMbidError;
+
+            fn try_from(value: String) -> Result<Self, Self::Error> {
+                ensure!(is_valid_mbid(&value), InvalidMbidSnafu { value });
+
+                Ok(Self(value.to_ascii_lowercase()))
+            }
+        }
+
+        impl TryFrom<&str> for $name {
+            type Error = InvalidMbidError;
+
+            fn try_from(value: &str) -> Result<Self, Self::Error> {
+                Self::try_from(value.to_string())
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = InvalidMbidError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                Self::try_from(s)
+            }
+        }
+
+        impl From<$name> for String {
+            fn from(value: $name) -> Self {
+                value.0
+            }
+        }
+
+        impl AsRef<str> for $name {
+            fn as_ref(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl Display for $name {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                write!(f, "{}", self.0)
+            }
+        }
+    };
+}
+
+uuid_newtype!(
+    /// The MBID of a MusicBrainz recording
+    RecordingMbid
+);
+
+uuid_newtype!(
+    /// The MBID of a MusicBrainz release
+    ReleaseMbid
+);
+
+uuid_newtype!(
+    /// The MBID of a MusicBrainz release group
+    ReleaseGroupMbid
+);
+
+uuid_newtype!(
+    /// The MBID of a MusicBrainz artist
+    ArtistMbid
+);
+
+uuid_newtype!(
+    /// The MessyBrainz ID of a recording. This is the ID given by ListenBrainz to the submitted track metadata
+    RecordingMsid
+);
+
+#[derive(Debug, Snafu)]
+#[snafu(display("`{value}` isn't a valid MBID"))]
+pub struct InvalidMbidError {
+    value: String,
+
+    #[snafu(implicit)]
+    location: snafu::Location,
+}
+
+#[cfg(test)]
+mod test {
+    use crate::models::mbid::RecordingMbid;
+    use crate::models::mbid::ReleaseMbid;
+
+    #[test]
+    fn mbid_validation_test() {
+        assert!(RecordingMbid::try_from("61c54b0e-3a82-49af-9cc7-73ff34365697").is_ok());
+        assert!(RecordingMbid::try_from("61c54b0e3a8249af9cc773ff34365697").is_err());
+        assert!(RecordingMbid::try_from("61c54b0e-3a82-49af-9cc7-73ff3436569z").is_err());
+
+        // MBIDs are normalized to lowercase
+        assert_eq!(
+            ReleaseMbid::try_from("61C54B0E-3A82-49AF-9CC7-73FF34365697")
+                .unwrap()
+                .as_str(),
+            "61c54b0e-3a82-49af-9cc7-73ff34365697"
+        );
+    }
+
+    #[test]
+    fn mbid_serde_test() {
+        let mbid: RecordingMbid =
+            serde_json::from_str("\"61c54b0e-3a82-49af-9cc7-73ff34365697\"").unwrap();
+        assert_eq!(
+            serde_json::to_string(&mbid).unwrap(),
+            "\"61c54b0e-3a82-49af-9cc7-73ff34365697\""
+        );
+
+        assert!(serde_json::from_str::<RecordingMbid>("\"not an mbid\"").is_err());
+    }
+}
@@ -1 +1,3 @@
+pub mod mbid;
+pub mod timestamp;
 pub mod token;
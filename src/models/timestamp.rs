@@ -0,0 +1,84 @@
+use core::fmt::Display;
+
+use chrono::DateTime;
+use chrono::Utc;
+use serde::Deserialize;
+use serde::Serialize;
+
+/// A UNIX timestamp in seconds, as used by the ListenBrainz API.
+///
+/// It can be created from a [`DateTime<Utc>`], or from raw seconds as `i64` (like the timestamps of the listens) or `u64`.
+/// Integer literals need a suffix to pick one (ex: `1_705_000_000_i64`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Timestamp(i64);
+
+impl Timestamp {
+    /// Create a timestamp from a number of seconds since the UNIX epoch
+    pub const fn from_secs(secs: i64) -> Self {
+        Self(secs)
+    }
+
+    /// The current time
+    pub fn now() -> Self {
+        Self::from(Utc::now())
+    }
+
+    /// The number of seconds since the UNIX epoch
+    pub const fn as_secs(&self) -> i64 {
+        self.0
+    }
+
+    /// The number of seconds since the UNIX epoch. Timestamps before the epoch are clamped to 0
+    pub fn as_unsigned_secs(&self) -> u64 {
+        u64::try_from(self.0).unwrap_or_default()
+    }
+
+    /// Convert the timestamp into a [`DateTime<Utc>`]. Return `None` if the timestamp is out of range
+    pub fn to_datetime(&self) -> Option<DateTime<Utc>> {
+        DateTime::from_timestamp(self.0, 0)
+    }
+}
+
+impl From<i64> for Timestamp {
+    fn from(value: i64) -> Self {
+        Self(value)
+    }
+}
+
+impl From<u64> for Timestamp {
+    fn from(value: u64) -> Self {
+        Self(i64::try_from(value).unwrap_or(i64::MAX))
+    }
+}
+
+impl From<DateTime<Utc>> for Timestamp {
+    fn from(value: DateTime<Utc>) -> Self {
+        Self(value.timestamp())
+    }
+}
+
+impl Display for Timestamp {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::DateTime;
+
+    use crate::models::timestamp::Timestamp;
+
+    #[test]
+    fn timestamp_conversion_test() {
+        let date = DateTime::from_timestamp(1_705_000_000, 0).unwrap();
+
+        assert_eq!(Timestamp::from(date), Timestamp::from(1_705_000_000_i64));
+        assert_eq!(Timestamp::from(1_705_000_000_u64).as_secs(), 1_705_000_000);
+        assert_eq!(Timestamp::from(date).to_datetime(), Some(date));
+
+        assert_eq!(Timestamp::from_secs(-5).as_unsigned_secs(), 0);
+        assert_eq!(Timestamp::from(u64::MAX).as_secs(), i64::MAX);
+    }
+}
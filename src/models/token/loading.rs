@@ -0,0 +1,184 @@
+use std::path::Path;
+use std::path::PathBuf;
+
+use snafu::OptionExt as _;
+use snafu::ResultExt as _;
+use snafu::Snafu;
+
+use crate::api::BaseUrlError;
+use crate::models::token::UserToken;
+
+/// The environment variable read by [`UserToken::from_default_env`], and the key looked up in dotfiles
+pub const TOKEN_ENV_VAR: &str = "LISTENBRAINZ_TOKEN";
+
+impl UserToken {
+    /// Read the token from an environment variable
+    pub fn from_env(var: &str) -> Result<Self, TokenLoadingError> {
+        let token = std::env::var(var).context(EnvVarSnafu { var })?;
+
+        Self::from_raw(&token).context(EmptyTokenSnafu {
+            origin: format!("the environment variable `{var}`"),
+        })
+    }
+
+    /// Read the token from the `LISTENBRAINZ_TOKEN` environment variable
+    pub fn from_default_env() -> Result<Self, TokenLoadingError> {
+        Self::from_env(TOKEN_ENV_VAR)
+    }
+
+    /// Read the token from a dotfile.
+    ///
+    /// The file can either be a `.env` style file containing a `LISTENBRAINZ_TOKEN=<token>` line,
+    /// or a file only containing the token.
+    pub fn from_dotfile(path: impl AsRef<Path>) -> Result<Self, TokenLoadingError> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path).context(ReadFileSnafu { path })?;
+
+        Self::from_dotfile_content(&content).context(EmptyTokenSnafu {
+            origin: format!("the file `{}`", path.display()),
+        })
+    }
+
+    /// Find the token in the content of a dotfile
+    fn from_dotfile_content(content: &str) -> Option<Self> {
+        let lines = content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .collect::<Vec<_>>();
+
+        let env_line = lines.iter().find_map(|line| {
+            let (key, value) = line.split_once('=')?;
+            let key = key.trim().trim_start_matches("export ").trim();
+
+            (key == TOKEN_ENV_VAR).then_some(value)
+        });
+
+        match (env_line, lines.as_slice()) {
+            (Some(value), _) => Self::from_raw(value.trim().trim_matches(['"', '\''])),
+            (None, [token]) if !token.contains('=') => Self::from_raw(token),
+            _ => None,
+        }
+    }
+
+    /// Create a token from a raw string, refusing empty tokens
+    fn from_raw(token: &str) -> Option<Self> {
+        let token = token.trim();
+
+        (!token.is_empty()).then(|| Self(token.to_string()))
+    }
+}
+
+#[derive(Debug, Snafu)]
+#[snafu(visibility(pub(super)))]
+pub enum TokenLoadingError {
+    #[snafu(display("Couldn't read the environment variable `{var}`"))]
+    EnvVarError {
+        source: std::env::VarError,
+        var: String,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+
+    #[snafu(display("Couldn't read the file `{}`", path.display()))]
+    ReadFileError {
+        source: std::io::Error,
+        path: PathBuf,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+
+    #[snafu(display("No token found in {origin}"))]
+    EmptyToken {
+        origin: String,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+
+    #[snafu(display("Couldn't parse the JSON config"))]
+    JsonConfigError {
+        source: serde_json::Error,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+
+    #[cfg(feature = "toml")]
+    #[snafu(display("Couldn't parse the TOML config"))]
+    TomlConfigError {
+        source: toml::de::Error,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+
+    #[snafu(display("The config file `{}` has an unsupported format", path.display()))]
+    UnsupportedConfigFormat {
+        path: PathBuf,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+
+    #[snafu(display("The base url of the profile is invalid"))]
+    BaseUrlError {
+        source: BaseUrlError,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+
+    #[snafu(display("The profile `{name}` doesn't exist in the config"))]
+    UnknownProfile {
+        name: String,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+}
+
+#[cfg(test)]
+mod test {
+    use crate::models::token::UserToken;
+
+    #[test]
+    fn dotfile_content_test() {
+        let token = UserToken::from_dotfile_content(
+            "# ListenBrainz\nLISTENBRAINZ_TOKEN=\"abc\"\nOTHER=1\n",
+        );
+        assert_eq!(token, Some(UserToken::from("abc".to_string())));
+
+        let token = UserToken::from_dotfile_content("\n  abc  \n");
+        assert_eq!(token, Some(UserToken::from("abc".to_string())));
+
+        assert_eq!(UserToken::from_dotfile_content("OTHER=1"), None);
+        assert_eq!(UserToken::from_dotfile_content(""), None);
+    }
+}
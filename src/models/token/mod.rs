@@ -0,0 +1,242 @@
+use api_bindium::ApiRequest;
+use api_bindium::ApiRequestError;
+use api_bindium::endpoints::UriBuilderError;
+use serde::Deserialize;
+use serde::Serialize;
+use snafu::Snafu;
+
+use crate::api::validate_token::ValidateTokenResponse;
+use crate::parser::ResponseError;
+
+/// The name of the header holding the token
+const AUTHORIZATION: &str = "AUTHORIZATION";
+
+pub mod loading;
+pub mod profiles;
+
+/// API token for logged in requests
+///
+/// The token is redacted from its [`Debug`](core::fmt::Debug) output, so it doesn't leak into logs
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UserToken(String);
+
+impl UserToken {
+    /// Verify the validity of the token.
+    #[cfg(feature = "sync")]
+    pub fn verify(
+        &self,
+        client: &crate::ListenBrainzClient,
+    ) -> Result<ValidateTokenResponse, UserTokenError> {
+        use snafu::ResultExt as _;
+
+        let mut request = client
+            .endpoints()
+            .post_validate_token(self.clone())
+            .context(UriBuilderSnafu)?;
+
+        let res = client
+            .send_authenticated(&mut request)
+            .context(ApiRequestSnafu)?
+            .parse()
+            .context(ResponseSnafu)?;
+
+        if !res.valid {
+            return InvalidTokenSnafu { response: res }.fail();
+        }
+
+        Ok(res)
+    }
+
+    /// Verify the validity of the token.
+    #[cfg(feature = "async")]
+    pub async fn verify_async(
+        &self,
+        client: &crate::ListenBrainzClient,
+    ) -> Result<ValidateTokenResponse, UserTokenError> {
+        use snafu::ResultExt as _;
+
+        let mut request = client
+            .endpoints()
+            .post_validate_token(self.clone())
+            .context(UriBuilderSnafu)?;
+
+        let res = client
+            .send_authenticated_async(&mut request)
+            .await
+            .context(ApiRequestSnafu)?
+            .parse()
+            .context(ResponseSnafu)?;
+
+        if !res.valid {
+            return InvalidTokenSnafu { response: res }.fail();
+        }
+
+        Ok(res)
+    }
+
+    /// Add the token to the api request.
+    ///
+    /// The token is stored in plain text in the headers of the request, and [`ApiRequest`]'s [`Debug`](core::fmt::Debug) output
+    /// prints those headers. Prefer keeping the token in an [`AuthenticatedRequest`], which only adds it while the request is sent
+    pub fn add_authorization<P>(&self, request: &mut ApiRequest<P>) {
+        // TODO: Upstream authorization headers into `api_bindium`
+        request
+            .headers_mut()
+            .insert(AUTHORIZATION.to_string(), format!("Token {}", self.0));
+    }
+
+    /// Remove the token added by [`Self::add_authorization`]
+    pub fn remove_authorization<P>(request: &mut ApiRequest<P>) {
+        request.headers_mut().remove(AUTHORIZATION);
+    }
+}
+
+impl core::fmt::Debug for UserToken {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("UserToken").field(&"<redacted>").finish()
+    }
+}
+
+/// An api request that needs a token.
+///
+/// The token is kept apart from the request, and is only added to its headers while it is sent,
+/// so printing the request with `{:?}` doesn't leak the token
+#[derive(Debug, Clone)]
+pub struct AuthenticatedRequest<P> {
+    request: ApiRequest<P>,
+    token: UserToken,
+}
+
+impl<P> AuthenticatedRequest<P> {
+    pub fn new(request: ApiRequest<P>, token: UserToken) -> Self {
+        Self { request, token }
+    }
+
+    /// The request, without its authorization header
+    pub fn request(&self) -> &ApiRequest<P> {
+        &self.request
+    }
+
+    pub fn token(&self) -> &UserToken {
+        &self.token
+    }
+
+    /// Return the request with the token in its headers. The token is removed once the returned guard is dropped
+    pub fn authorize(&mut self) -> AuthorizedRequest<'_, P> {
+        self.token.add_authorization(&mut self.request);
+        AuthorizedRequest(&mut self.request)
+    }
+}
+
+/// An api request with its authorization header set. See [`AuthenticatedRequest::authorize`]
+pub struct AuthorizedRequest<'a, P>(&'a mut ApiRequest<P>);
+
+impl<P> core::ops::Deref for AuthorizedRequest<'_, P> {
+    type Target = ApiRequest<P>;
+
+    fn deref(&self) -> &Self::Target {
+        self.0
+    }
+}
+
+impl<P> core::ops::DerefMut for AuthorizedRequest<'_, P> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.0
+    }
+}
+
+impl<P> Drop for AuthorizedRequest<'_, P> {
+    fn drop(&mut self) {
+        UserToken::remove_authorization(self.0);
+    }
+}
+
+impl From<UserToken> for String {
+    fn from(value: UserToken) -> Self {
+        value.0
+    }
+}
+
+impl From<String> for UserToken {
+    fn from(value: String) -> Self {
+        Self(value)
+    }
+}
+
+#[derive(Debug, Snafu)]
+#[snafu(visibility(pub(crate)))]
+pub enum UserTokenError {
+    UriBuilderError {
+        source: UriBuilderError,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+
+    ApiRequestError {
+        source: ApiRequestError,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+
+    ResponseError {
+        source: ResponseError,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+
+    // Invalid token
+    InvalidToken {
+        response: ValidateTokenResponse,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+}
+
+#[cfg(test)]
+mod test {
+    use crate::api::ListenBrainzAPIEnpoints;
+    use crate::models::token::UserToken;
+
+    #[test]
+    fn redacted_debug_test() {
+        let token = UserToken::from("secret-token".to_string());
+
+        assert!(!format!("{token:?}").contains("secret-token"));
+
+        let mut request = ListenBrainzAPIEnpoints::default()
+            .post_validate_token(token)
+            .unwrap();
+        let debug = format!("{request:?}");
+        assert!(!debug.contains("secret-token"));
+        assert!(debug.contains("validate-token"));
+
+        // The token is only in the headers while the request is sent
+        {
+            let authorized = request.authorize();
+            assert_eq!(
+                authorized
+                    .headers()
+                    .get("AUTHORIZATION")
+                    .map(String::as_str),
+                Some("Token secret-token")
+            );
+        }
+        assert!(!format!("{request:?}").contains("secret-token"));
+    }
+}
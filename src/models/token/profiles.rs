@@ -0,0 +1,151 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use serde::Deserialize;
+use serde::Serialize;
+use snafu::OptionExt as _;
+use snafu::ResultExt as _;
+
+use crate::api::ListenBrainzAPIEnpoints;
+use crate::models::token::UserToken;
+use crate::models::token::loading::BaseUrlSnafu;
+use crate::models::token::loading::JsonConfigSnafu;
+use crate::models::token::loading::ReadFileSnafu;
+use crate::models::token::loading::TokenLoadingError;
+use crate::models::token::loading::UnknownProfileSnafu;
+use crate::models::token::loading::UnsupportedConfigFormatSnafu;
+
+/// A config file containing multiple named accounts.
+///
+/// In TOML, it looks like this:
+///
+/// ```toml
+/// [profiles.main]
+/// user_name = "RustyNova"
+/// token = "..."
+///
+/// [profiles.dev]
+/// user_name = "RustyNova"
+/// token = "..."
+/// base_url = "http://localhost:8100"
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TokenConfig {
+    #[serde(default)]
+    pub profiles: HashMap<String, TokenProfile>,
+}
+
+impl TokenConfig {
+    /// Parse a JSON config
+    pub fn from_json_str(config: &str) -> Result<Self, TokenLoadingError> {
+        serde_json::from_str(config).context(JsonConfigSnafu)
+    }
+
+    /// Parse a TOML config
+    #[cfg(feature = "toml")]
+    pub fn from_toml_str(config: &str) -> Result<Self, TokenLoadingError> {
+        use crate::models::token::loading::TomlConfigSnafu;
+
+        toml::from_str(config).context(TomlConfigSnafu)
+    }
+
+    /// Read a config file. The format is chosen from the extension of the file (`.json`, or `.toml` with the `toml` feature)
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, TokenLoadingError> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path).context(ReadFileSnafu { path })?;
+
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("json") => Self::from_json_str(&content),
+            #[cfg(feature = "toml")]
+            Some("toml") => Self::from_toml_str(&content),
+            _ => UnsupportedConfigFormatSnafu { path }.fail(),
+        }
+    }
+
+    /// Get a profile by its name
+    pub fn profile(&self, name: &str) -> Result<&TokenProfile, TokenLoadingError> {
+        self.profiles
+            .get(name)
+            .context(UnknownProfileSnafu { name })
+    }
+}
+
+/// An account of a [`TokenConfig`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenProfile {
+    /// The name of the user owning the token
+    pub user_name: String,
+
+    /// The token of the user
+    pub token: UserToken,
+
+    /// The base url of the ListenBrainz server, with its scheme. Default to the official server.
+    ///
+    /// See [`ListenBrainzAPIEnpoints::from_base_url`]
+    #[serde(default)]
+    pub base_url: Option<String>,
+}
+
+impl TokenProfile {
+    /// Create the endpoints of the server of the profile
+    pub fn endpoints(&self) -> Result<ListenBrainzAPIEnpoints, TokenLoadingError> {
+        match &self.base_url {
+            Some(base_url) => {
+                ListenBrainzAPIEnpoints::from_base_url(base_url).context(BaseUrlSnafu)
+            }
+            None => Ok(ListenBrainzAPIEnpoints::default()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::models::token::profiles::TokenConfig;
+
+    #[test]
+    fn json_config_test() {
+        let config = TokenConfig::from_json_str(
+            r#"{
+                "profiles": {
+                    "main": { "user_name": "RustyNova", "token": "abc" },
+                    "dev": { "user_name": "RustyNova", "token": "def", "base_url": "http://localhost:8100/listenbrainz" },
+                    "broken": { "user_name": "RustyNova", "token": "ghi", "base_url": "localhost:8100" }
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let dev = config.profile("dev").unwrap();
+        assert_eq!(dev.user_name, "RustyNova");
+        assert_eq!(
+            dev.endpoints().unwrap().api_root(),
+            "http://localhost:8100/listenbrainz"
+        );
+        assert_eq!(
+            config
+                .profile("main")
+                .unwrap()
+                .endpoints()
+                .unwrap()
+                .api_root(),
+            "https://api.listenbrainz.org"
+        );
+        assert!(config.profile("broken").unwrap().endpoints().is_err());
+        assert!(config.profile("missing").is_err());
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn toml_config_test() {
+        let config = TokenConfig::from_toml_str(
+            r#"
+            [profiles.main]
+            user_name = "RustyNova"
+            token = "abc"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.profile("main").unwrap().user_name, "RustyNova");
+    }
+}
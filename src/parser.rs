@@ -0,0 +1,250 @@
+use core::marker::PhantomData;
+
+use api_bindium::ApiRequestError;
+use api_bindium::Parser;
+use api_bindium::api_response::ureq_response::UreqResponseInner;
+use api_bindium::ureq;
+use api_bindium::ureq::Body;
+use api_bindium::ureq::ResponseExt as _;
+use api_bindium::ureq::http::Response;
+use api_bindium::ureq::http::Uri;
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use snafu::GenerateImplicitData as _;
+use snafu::ResultExt as _;
+use snafu::Snafu;
+
+/// Parse a json response into T, decoding the error bodies returned by ListenBrainz.
+///
+/// Unsuccessful responses are returned as a [`ResponseError::ListenBrainzApiError`] containing the reason given by the server,
+/// or a [`ResponseError::HttpStatusError`] if the body isn't a ListenBrainz error
+#[derive(Debug)]
+pub struct ListenBrainzParser<T>(PhantomData<T>)
+where
+    T: Sized + DeserializeOwned;
+
+impl<T> Parser<UreqResponseInner> for ListenBrainzParser<T>
+where
+    T: Sized + DeserializeOwned,
+{
+    type Output = T;
+    type Error = ResponseError;
+
+    fn parse(&self, mut response: UreqResponseInner) -> Result<Self::Output, Self::Error> {
+        let status = response.data.status();
+        let text = read_text(&mut response).context(ApiRequestSnafu)?;
+
+        if status.is_success() {
+            return serde_json::from_str::<T>(&text)
+                .map_err(|source| ApiRequestError::JsonParsingError {
+                    source,
+                    data: text,
+                    location: snafu::Location::generate(),
+                    #[cfg(feature = "backtrace")]
+                    backtrace: snafu::Backtrace::generate(),
+                })
+                .context(ApiRequestSnafu);
+        }
+
+        // Not all errors come from ListenBrainz (ex: A proxy in front of it). Fallback on the status and raw body
+        let Ok(body) = serde_json::from_str::<ApiErrorBody>(&text) else {
+            return HttpStatusSnafu {
+                status: status.as_u16(),
+                body: text,
+            }
+            .fail();
+        };
+
+        ListenBrainzApiSnafu {
+            kind: ApiErrorKind::classify(body.code, &body.error),
+            code: body.code,
+            message: body.error,
+        }
+        .fail()
+    }
+}
+
+impl<T> Default for ListenBrainzParser<T>
+where
+    T: Sized + DeserializeOwned,
+{
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+/// Read the body of the response as text.
+///
+/// Unlike [`api_bindium::TextParser`], this also works on the responses replayed from a cassette or a cache,
+/// as they don't have the uri set by ureq on the responses it received
+fn read_text(response: &mut UreqResponseInner) -> Result<String, ApiRequestError> {
+    let limit = response.max_body_size;
+
+    match response
+        .data
+        .body_mut()
+        .with_config()
+        .limit(limit)
+        .read_to_string()
+    {
+        Ok(text) => Ok(text),
+        Err(source) => Err(ureq_error(response_uri(&response.data), source)),
+    }
+}
+
+/// The uri of a response replayed from a cassette or a cache
+#[derive(Debug, Clone)]
+pub(crate) struct ReplayedUri(pub Uri);
+
+/// Return the uri the response was fetched from
+fn response_uri(response: &Response<Body>) -> Uri {
+    match response.extensions().get::<ReplayedUri>() {
+        Some(uri) => uri.0.clone(),
+        None => response.get_uri().clone(),
+    }
+}
+
+/// Create an [`ApiRequestError`] for an error of ureq. `api_bindium` doesn't expose its context selectors, so this is done by hand
+pub(crate) fn ureq_error(uri: Uri, source: ureq::Error) -> ApiRequestError {
+    ApiRequestError::UreqError {
+        source,
+        uri,
+        location: snafu::Location::generate(),
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace::generate(),
+    }
+}
+
+/// The body of an error returned by ListenBrainz
+#[derive(Debug, Deserialize)]
+struct ApiErrorBody {
+    code: u16,
+    error: String,
+}
+
+/// The common reasons ListenBrainz refuses a request
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApiErrorKind {
+    /// The token is missing or invalid
+    InvalidToken,
+
+    /// The requested user doesn't exist
+    UserNotFound,
+
+    /// Too many requests got sent
+    RateLimited,
+
+    /// The body of the request is too large
+    PayloadTooLarge,
+
+    /// A timestamp of the request is invalid
+    InvalidTimestamp,
+
+    /// Any other error
+    Other,
+}
+
+impl ApiErrorKind {
+    /// Find the kind of error from the code and message returned by the server
+    pub fn classify(code: u16, message: &str) -> Self {
+        let message = message.to_lowercase();
+
+        match code {
+            401 => Self::InvalidToken,
+            429 => Self::RateLimited,
+            413 => Self::PayloadTooLarge,
+            404 if message.contains("user") => Self::UserNotFound,
+            400 if message.contains("too large") => Self::PayloadTooLarge,
+            400 if message.contains("timestamp")
+                || message.contains("listened_at")
+                || message.contains("_ts") =>
+            {
+                Self::InvalidTimestamp
+            }
+            _ => Self::Other,
+        }
+    }
+}
+
+#[derive(Debug, Snafu)]
+#[snafu(visibility(pub(crate)))]
+pub enum ResponseError {
+    /// ListenBrainz refused the request
+    #[snafu(display("ListenBrainz returned an error ({code}): {message}"))]
+    ListenBrainzApiError {
+        /// The HTTP status code of the error
+        code: u16,
+
+        /// The reason given by the server
+        message: String,
+
+        kind: ApiErrorKind,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+
+    /// The server responded with an unsuccessful status, without a ListenBrainz error body
+    #[snafu(display("The server responded with the HTTP status {status}"))]
+    HttpStatusError {
+        status: u16,
+
+        /// The raw body of the response
+        body: String,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+
+    ApiRequestError {
+        source: ApiRequestError,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+}
+
+impl ResponseError {
+    /// Return the kind of error returned by ListenBrainz, if the server refused the request
+    pub fn api_error_kind(&self) -> Option<ApiErrorKind> {
+        match self {
+            Self::ListenBrainzApiError { kind, .. } => Some(*kind),
+            Self::HttpStatusError { .. } | Self::ApiRequestError { .. } => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::parser::ApiErrorKind;
+
+    #[test]
+    fn classify_api_error_test() {
+        assert_eq!(
+            ApiErrorKind::classify(401, "Invalid authorization token."),
+            ApiErrorKind::InvalidToken
+        );
+        assert_eq!(
+            ApiErrorKind::classify(404, "Cannot find user: nobody"),
+            ApiErrorKind::UserNotFound
+        );
+        assert_eq!(
+            ApiErrorKind::classify(400, "Value for key listened_at is too low."),
+            ApiErrorKind::InvalidTimestamp
+        );
+        assert_eq!(
+            ApiErrorKind::classify(400, "Payload too large."),
+            ApiErrorKind::PayloadTooLarge
+        );
+        assert_eq!(ApiErrorKind::classify(500, "Oops"), ApiErrorKind::Other);
+    }
+}